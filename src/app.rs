@@ -1,22 +1,358 @@
+use crate::clipboard;
+use crate::config::Config;
+use crate::epics;
+use crate::export::{self, Filter, Format};
+use crate::external_editor;
+use crate::flow;
+use crate::forecast::{self, Forecast};
+use crate::git;
 use crate::io;
-use ratatui::style::Style;
+use crate::keymap::{Key, Keymap};
+use crate::locations::{self, Location};
+use crate::markdown;
+use crate::modes::{Command, Mode};
+use crate::palette::{self, PaletteCommand};
+use crate::quick_add;
+use crate::recurrence::Recurrence;
+use crate::scan;
+use crate::sprint::{self, Sprint};
+use crate::templates::Template;
+use crate::theme::Theme;
+use crate::timesheet::{self, TimeEntry};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use ratatui::{layout::Rect, style::Style, widgets::ListState};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 use tui_textarea::TextArea;
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
 pub enum Status {
+    #[default]
     Todo,
     Doing,
     Done,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl Status {
+    /// In column order.
+    pub const ALL: [Status; 3] = [Status::Todo, Status::Doing, Status::Done];
+
+    /// Index of the board column that shows this status.
+    pub fn column(&self) -> usize {
+        match self {
+            Status::Todo => 0,
+            Status::Doing => 1,
+            Status::Done => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Todo => "todo",
+            Status::Doing => "doing",
+            Status::Done => "done",
+        }
+    }
+
+    /// Column title: `TODO`, `DOING`, `DONE`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Todo => "TODO",
+            Status::Doing => "DOING",
+            Status::Done => "DONE",
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "todo" => Ok(Status::Todo),
+            "doing" => Ok(Status::Doing),
+            "done" => Ok(Status::Done),
+            _ => bail!("Unknown status '{}' (expected todo, doing or done)", s),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Task {
+    /// Stable, never reused; 0 until the task is first saved.
+    #[serde(default)]
+    pub id: u64,
     pub title: String,
     #[serde(default)]
     pub description: String,
     pub status: Status,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// 1 is the most urgent (`p1`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// Who works on it, without the `@`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// Story points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// Finishing the task adds the next one to TODO.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<Recurrence>,
+    /// Tracked with the timer; the last one may still run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_entries: Vec<TimeEntry>,
+    /// Name of the sprint the task is planned into.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprint: Option<String>,
+    /// ID of the epic the task is part of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u64>,
+    /// Every status the task had and since when, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<Transition>,
+    /// Set for tasks imported from a source comment by `scan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Fields from newer versions, written back unchanged.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Task {
+    pub fn new(title: impl Into<String>, status: Status) -> Self {
+        let now = Utc::now();
+        Task {
+            title: title.into(),
+            transitions: vec![Transition {
+                at: now,
+                status: status.clone(),
+            }],
+            status,
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..Default::default()
+        }
+    }
+
+    /// Moves the task and records the transition.
+    pub fn set_status(&mut self, status: Status) {
+        self.touch();
+        self.transitions.push(Transition {
+            at: self.updated_at.unwrap_or_else(Utc::now),
+            status: status.clone(),
+        });
+        self.status = status;
+    }
+
+    /// The status the task had at `time`, `None` before it was created.
+    /// Tasks saved before transitions were recorded count as done from
+    /// their last update and as TODO before that.
+    pub fn status_at(&self, time: DateTime<Utc>) -> Option<Status> {
+        if let Some(t) = self.transitions.iter().rev().find(|t| t.at <= time) {
+            return Some(t.status.clone());
+        }
+        if !self.transitions.is_empty() || self.created_at.is_some_and(|c| c > time) {
+            return None;
+        }
+        let done = self.status == Status::Done && self.last_update().is_some_and(|u| u <= time);
+        Some(if done { Status::Done } else { Status::Todo })
+    }
+
+    /// Records a change to the task.
+    pub fn touch(&mut self) {
+        self.updated_at = Some(Utc::now());
+    }
+
+    /// Last change, falling back to creation for tasks saved before
+    /// `updated_at` existed.
+    pub fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.updated_at.or(self.created_at)
+    }
+}
+
+/// A status change, see `Task::set_status`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub at: DateTime<Utc>,
+    pub status: Status,
+}
+
+/// Where a scanned task came from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SourceRef {
+    pub path: String,
+    pub line: usize,
+    pub marker: String,
+    pub text: String,
+    /// `HEAD` when `line` was recorded, used to follow renames and shifts.
+    /// `None` if the file had uncommitted changes then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The comment was not found on the last scan.
+    #[serde(default)]
+    pub missing: bool,
+    /// Hash of the description the scan wrote, so a rescan only replaces
+    /// it while it is unedited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Board-wide data stored next to the tasks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Board {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// ID of the next new task.
+    #[serde(default)]
+    pub next_id: u64,
+    /// Oldest first; at most the last one is running.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sprints: Vec<Sprint>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Board {
+    /// Gives every task without an ID (new or hand-written) the next one.
+    pub fn assign_ids(&mut self, tasks: &mut [Task]) {
+        let max = tasks.iter().map(|t| t.id).max().unwrap_or(0);
+        self.next_id = self.next_id.max(max + 1);
+        for task in tasks.iter_mut().filter(|t| t.id == 0) {
+            task.id = self.next_id;
+            self.next_id += 1;
+        }
+    }
+}
+
+/// How tasks are drawn in the columns.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// One line per task.
+    #[default]
+    Compact,
+    /// A bordered box with the wrapped title, a description preview and
+    /// a metadata row.
+    Cards,
+}
+
+/// How the three columns share the screen.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnLayout {
+    /// Side by side, or tabs when the terminal is narrow.
+    #[default]
+    Auto,
+    Columns,
+    /// One column at a time with tabs in the header.
+    Tabs,
+    /// On top of each other.
+    Stack,
+}
+
+impl ColumnLayout {
+    /// Below this width `Auto` switches to tabs.
+    pub const NARROW_WIDTH: u16 = 80;
+
+    /// `Auto` decided for a board area `width` columns wide.
+    pub fn resolve(self, width: u16) -> ColumnLayout {
+        match self {
+            ColumnLayout::Auto if width < Self::NARROW_WIDTH => ColumnLayout::Tabs,
+            ColumnLayout::Auto => ColumnLayout::Columns,
+            other => other,
+        }
+    }
+}
+
+/// What `Task::estimate` counts.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EstimateUnit {
+    /// Story points.
+    #[default]
+    Points,
+    Hours,
+}
+
+impl EstimateUnit {
+    /// `3pt` or `3h`.
+    pub fn format(self, estimate: u32) -> String {
+        match self {
+            EstimateUnit::Points => format!("{}pt", estimate),
+            EstimateUnit::Hours => format!("{}h", estimate),
+        }
+    }
+}
+
+/// How long a status line message stays visible.
+pub const STATUS_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Scroll state of the `?` overlay; `max` and `page` are set by `ui::render`.
+#[derive(Default)]
+pub struct HelpOverlay {
+    pub scroll: u16,
+    pub max: u16,
+    pub page: u16,
+}
+
+pub struct StatusMessage {
+    pub text: String,
+    pub error: bool,
+    pub shown_at: Instant,
+}
+
+/// The `:` command line in the footer.
+pub struct Palette<'a> {
+    pub editor: TextArea<'a>,
+    /// Entry of `App::history` being shown by Up/Down.
+    history_pos: Option<usize>,
+    pub completion: Option<Completion>,
+}
+
+/// Tab completion in progress: the word from `start` on is replaced by
+/// each candidate in turn.
+pub struct Completion {
+    start: usize,
+    pub candidates: Vec<String>,
+    pub index: usize,
+}
+
+/// The "jump to location" list opened from the view modal.
+pub struct LocationPicker {
+    pub root: PathBuf,
+    pub items: Vec<Location>,
+    pub selected: usize,
+}
+
+/// Export the board to a file or the clipboard.
+pub struct ExportDialog<'a> {
+    pub format: Format,
+    pub path: TextArea<'a>,
+}
+
+/// A card picked up with the mouse, waiting to be dropped.
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    pub column: usize,
+    pub index: usize,
+    pub over: Option<usize>, // Column under the pointer
 }
 
 #[derive(Debug, PartialEq)]
@@ -27,73 +363,373 @@ pub enum EditFocus {
 
 pub struct App<'a> {
     pub tasks: Vec<Task>,
+    pub board: Board,
+    envelope: io::Envelope,
     pub active_column: usize,
     pub selected_index: usize,
 
-    // Modes
-    pub input_mode: bool,  // Footer Quick Add
-    pub view_mode: bool,   // Read-only Modal
-    pub delete_mode: bool, // Delete Confirm
-    pub edit_mode: bool,   // Split Edit Modal
+    /// Open modals, innermost last. Empty on the board.
+    pub modes: Vec<Mode>,
 
     pub edit_focus: EditFocus, // Which box is active in edit mode?
+    pub view_checkbox: usize,  // Highlighted checkbox in view mode
+    pub view_child: usize,     // Highlighted child task in view mode
+    pub view_scroll: u16,      // First visible description line in view mode
+    /// Set by `ui::render`: the last scroll position and the page height.
+    pub view_scroll_max: u16,
+    pub view_height: u16,
+    /// Scroll the highlighted checkbox into view on the next render.
+    pub view_follow_checkbox: bool,
+
+    // $EDITOR failed or returned unparsable text: (error, text to retry with)
+    pub external_error: Option<(String, String)>,
+    /// Transient message in the status line.
+    pub status: Option<StatusMessage>,
+    /// Words every shown task must match, see `matches_filter`.
+    pub filter: String,
+    pub filter_editor: TextArea<'a>,
+    /// Key binding overlay, open when set.
+    pub help: Option<HelpOverlay>,
+    pub location_picker: Option<LocationPicker>,
+    pub export_dialog: Option<ExportDialog<'a>>,
+    pub palette: Option<Palette<'a>>,
+    pub templates: Vec<Template>,
+    /// Saved views from the config, see `:board`.
+    pub boards: HashMap<String, String>,
+    /// Highlighted entry of the "new from template" picker.
+    pub template_picker: Option<usize>,
+    /// Template that quick add fills the new task from.
+    pub quick_add_template: Option<usize>,
+    /// Entry of `flow::RANGES` shown by the analytics screen, open when set.
+    pub analytics_range: Option<usize>,
+    /// Shown by the forecast panel, open when set.
+    pub forecast: Option<Forecast>,
+    /// Highlighted row of the task tree, open when set.
+    pub tree_selected: Option<usize>,
+    /// Earlier command lines, oldest first.
+    history: Vec<String>,
 
     pub file_path: PathBuf,
 
     // EDITORS
     pub title_editor: TextArea<'a>,
     pub description_editor: TextArea<'a>,
+
+    // KEYS
+    pub keymap: Keymap,
+    pub pending_keys: Vec<Key>, // Typed prefix of a multi-key binding (e.g. "g" of "gg")
+
+    pub theme: Theme,
+
+    // MOUSE (areas are filled in by ui::render for hit-testing)
+    pub column_areas: [Rect; 3],
+    pub list_states: [ListState; 3],
+    pub density: Density,
+    pub layout: ColumnLayout,
+    pub estimate_unit: EstimateUnit,
+    /// Only the active column is shown, whatever the layout.
+    pub zoomed: bool,
+    /// Header tabs, set by `ui::render` when a single column is shown.
+    pub tab_areas: [Rect; 3],
+    /// Rendered height of every item per column, for hit-testing and scrolling.
+    pub item_heights: [Vec<u16>; 3],
+    pub last_click: Option<(Instant, usize, usize)>,
+    pub drag: Option<Drag>,
 }
 
 impl<'a> App<'a> {
-    pub fn new(file_path: PathBuf) -> Self {
-        let tasks = io::load_tasks(&file_path);
+    pub fn new(file_path: PathBuf, config: &Config) -> Result<Self> {
+        let keymap = Keymap::from_config(&config.keys)?;
+        let theme = Theme::from_config(config)?;
+        let doc = io::load(&file_path)?;
 
         let mut title_ta = TextArea::default();
         title_ta.set_cursor_line_style(Style::default());
 
         let desc_ta = TextArea::default();
 
-        App {
-            tasks,
+        Ok(App {
+            tasks: doc.tasks,
+            board: doc.board,
+            envelope: doc.envelope,
             active_column: 0,
             selected_index: 0,
 
-            input_mode: false,
-            view_mode: false,
-            delete_mode: false,
-            edit_mode: false,
+            modes: Vec::new(),
             edit_focus: EditFocus::Title,
+            view_checkbox: 0,
+            view_child: 0,
+            view_scroll: 0,
+            view_scroll_max: 0,
+            view_height: 0,
+            view_follow_checkbox: false,
+            external_error: None,
+            status: None,
+            filter: String::new(),
+            filter_editor: TextArea::default(),
+            help: None,
+            location_picker: None,
+            export_dialog: None,
+            palette: None,
+            templates: Template::from_config(config)?,
+            boards: config.boards.clone(),
+            template_picker: None,
+            quick_add_template: None,
+            analytics_range: None,
+            forecast: None,
+            tree_selected: None,
+            history: palette::load_history(&palette::history_path(&file_path)),
 
             file_path,
 
             title_editor: title_ta,
             description_editor: desc_ta,
+
+            keymap,
+            pending_keys: Vec::new(),
+
+            theme,
+
+            column_areas: [Rect::default(); 3],
+            list_states: Default::default(),
+            density: config.density,
+            layout: config.layout,
+            estimate_unit: config.estimate_unit,
+            zoomed: false,
+            tab_areas: [Rect::default(); 3],
+            item_heights: Default::default(),
+            last_click: None,
+            drag: None,
+        })
+    }
+
+    fn save(&mut self) {
+        self.board.assign_ids(&mut self.tasks);
+        if let Err(e) = io::save(&self.file_path, &self.envelope, &self.board, &self.tasks) {
+            self.flash_error(format!("Saving failed: {:#}", e));
+        }
+    }
+
+    // --- STATUS LINE ---
+    pub fn flash(&mut self, text: impl Into<String>) {
+        self.status = Some(StatusMessage {
+            text: text.into(),
+            error: false,
+            shown_at: Instant::now(),
+        });
+    }
+    pub fn flash_error(&mut self, text: impl Into<String>) {
+        self.status = Some(StatusMessage {
+            text: text.into(),
+            error: true,
+            shown_at: Instant::now(),
+        });
+    }
+    /// The current message, unless it has timed out.
+    pub fn status_message(&self) -> Option<&StatusMessage> {
+        self.status
+            .as_ref()
+            .filter(|m| m.shown_at.elapsed() < STATUS_TIMEOUT)
+    }
+    /// Time until the current message should disappear.
+    pub fn status_timeout(&self) -> Option<Duration> {
+        self.status_message()
+            .map(|m| STATUS_TIMEOUT.saturating_sub(m.shown_at.elapsed()))
+    }
+
+    /// `board.name`, or the directory the board belongs to.
+    pub fn board_name(&self) -> String {
+        if !self.board.name.is_empty() {
+            return self.board.name.clone();
         }
+        let dir = self.file_path.parent();
+        let dir = match dir {
+            Some(d) if d.file_name().is_some_and(|n| n == ".git") => d.parent(),
+            other => other,
+        };
+        dir.and_then(|d| d.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "kanban".to_string())
     }
 
-    fn save(&self) {
-        if let Err(e) = io::save_tasks(&self.file_path, &self.tasks) {
-            eprintln!("Error saving tasks: {}", e);
+    // --- MODES ---
+    /// The mode that receives keys: the innermost modal, or the board.
+    pub fn mode(&self) -> Mode {
+        self.modes.last().copied().unwrap_or(Mode::Board)
+    }
+    pub fn is_open(&self, mode: Mode) -> bool {
+        self.modes.contains(&mode)
+    }
+    fn open(&mut self, mode: Mode) {
+        self.modes.push(mode);
+    }
+    /// Closes `mode` and anything opened from it, returning to its caller.
+    fn close(&mut self, mode: Mode) {
+        if let Some(pos) = self.modes.iter().rposition(|m| *m == mode) {
+            self.modes.truncate(pos);
         }
     }
 
+    // --- HELP ---
+    pub fn open_help(&mut self) {
+        self.help = Some(HelpOverlay::default());
+        self.open(Mode::Help);
+    }
+    pub fn close_help(&mut self) {
+        self.help = None;
+        self.close(Mode::Help);
+    }
+    pub fn scroll_help(&mut self, command: Command) {
+        let Some(help) = &mut self.help else {
+            return;
+        };
+        let page = help.page.saturating_sub(1).max(1) as isize;
+        let delta = match command {
+            Command::ScrollUp => -1,
+            Command::ScrollDown => 1,
+            Command::PageUp => -page,
+            Command::PageDown => page,
+            _ => 0,
+        };
+        help.scroll = (help.scroll as isize + delta).clamp(0, help.max as isize) as u16;
+    }
+
+    // --- SPRINT ---
+    pub fn open_sprint(&mut self) {
+        if self.board.sprints.is_empty() {
+            self.flash("No sprints yet (start one with 'git kanban sprint start NAME')");
+            return;
+        }
+        self.open(Mode::Sprint);
+    }
+    pub fn close_sprint(&mut self) {
+        self.close(Mode::Sprint);
+    }
+
+    // --- ANALYTICS ---
+    pub fn open_analytics(&mut self) {
+        self.analytics_range = Some(1);
+        self.open(Mode::Analytics);
+    }
+    pub fn close_analytics(&mut self) {
+        self.analytics_range = None;
+        self.close(Mode::Analytics);
+    }
+    pub fn next_analytics_range(&mut self) {
+        if let Some(i) = &mut self.analytics_range {
+            *i = (*i + 1) % flow::RANGES.len();
+        }
+    }
+
+    // --- TREE ---
+    /// Starts on the selected task.
+    pub fn open_tree(&mut self) {
+        let selected = self.get_selected_global_index();
+        let rows = epics::tree(&self.tasks);
+        let row = rows.iter().position(|&(i, _)| Some(i) == selected);
+        self.tree_selected = Some(row.unwrap_or(0));
+        self.open(Mode::Tree);
+    }
+    pub fn close_tree(&mut self) {
+        self.tree_selected = None;
+        self.close(Mode::Tree);
+    }
+    pub fn move_in_tree(&mut self, delta: isize) {
+        let last = self.tasks.len().saturating_sub(1) as isize;
+        if let Some(row) = &mut self.tree_selected {
+            *row = (*row as isize + delta).clamp(0, last) as usize;
+        }
+    }
+    /// Closes the tree on the highlighted task.
+    pub fn open_tree_task(&mut self) {
+        let rows = epics::tree(&self.tasks);
+        if let Some(&(idx, _)) = self.tree_selected.and_then(|row| rows.get(row)) {
+            self.reveal(idx);
+        }
+        self.close_tree();
+    }
+
+    // --- FORECAST ---
+    pub fn open_forecast(&mut self) {
+        let today = Local::now().date_naive();
+        let (items, by) = forecast::defaults(&self.board, &self.tasks, today);
+        self.run_forecast(items.max(1), by);
+        self.open(Mode::Forecast);
+    }
+    pub fn close_forecast(&mut self) {
+        self.forecast = None;
+        self.close(Mode::Forecast);
+    }
+    /// Asks about `items` more tasks and a date `days` later.
+    pub fn adjust_forecast(&mut self, items: i64, days: i64) {
+        let Some(current) = &self.forecast else {
+            return;
+        };
+        let today = Local::now().date_naive();
+        let items = (current.items as i64 + items).max(1) as u32;
+        let last = today + chrono::Duration::days(forecast::MAX_DAYS as i64);
+        let by = (current.by + chrono::Duration::days(days))
+            .clamp(today + chrono::Duration::days(1), last);
+        if let Some(forecast) = &mut self.forecast {
+            forecast.ask(today, items, by);
+        }
+    }
+    fn run_forecast(&mut self, items: u32, by: NaiveDate) {
+        let today = Local::now().date_naive();
+        let history = forecast::throughput(&self.tasks, forecast::HISTORY_DAYS, today);
+        self.forecast = Some(Forecast::new(&history, today, items, by));
+    }
+
+    // --- FILTER ---
+    pub fn start_filter(&mut self) {
+        self.open(Mode::Filter);
+        self.filter_editor = TextArea::new(vec![self.filter.clone()]);
+        self.filter_editor.set_cursor_line_style(Style::default());
+        self.filter_editor
+            .move_cursor(tui_textarea::CursorMove::End);
+    }
+    /// Applies the typed filter as it changes.
+    pub fn update_filter(&mut self) {
+        self.filter = self.filter_editor.lines().join(" ").trim().to_string();
+        self.selected_index = 0;
+    }
+    pub fn close_filter(&mut self) {
+        self.close(Mode::Filter);
+    }
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.close(Mode::Filter);
+        self.selected_index = 0;
+    }
+
     // --- HELPERS ---
     pub fn get_tasks_in_column(&self, col_idx: usize) -> Vec<&Task> {
-        let status = match col_idx {
-            0 => Status::Todo,
-            1 => Status::Doing,
-            _ => Status::Done,
-        };
-        self.tasks.iter().filter(|t| t.status == status).collect()
+        self.column_indices(col_idx)
+            .into_iter()
+            .map(|i| &self.tasks[i])
+            .collect()
+    }
+
+    /// Tasks in a column whether or not they match the filter.
+    pub fn column_total(&self, col_idx: usize) -> usize {
+        let status = column_status(col_idx);
+        self.tasks.iter().filter(|t| t.status == status).count()
+    }
+
+    /// Positions in `self.tasks` of the tasks shown in a column, in order.
+    fn column_indices(&self, col_idx: usize) -> Vec<usize> {
+        let status = column_status(col_idx);
+        (0..self.tasks.len())
+            .filter(|&i| {
+                self.tasks[i].status == status && matches_filter(&self.tasks[i], &self.filter)
+            })
+            .collect()
     }
 
     fn get_selected_global_index(&self) -> Option<usize> {
-        let tasks_in_col = self.get_tasks_in_column(self.active_column);
-        let task_ref = tasks_in_col.get(self.selected_index)?;
-        self.tasks
-            .iter()
-            .position(|t| t.title == task_ref.title && t.status == task_ref.status)
+        self.column_indices(self.active_column)
+            .get(self.selected_index)
+            .copied()
     }
 
     // --- NAVIGATION ---
@@ -120,6 +756,129 @@ impl<'a> App<'a> {
             self.selected_index -= 1;
         }
     }
+    pub fn first_item(&mut self) {
+        self.selected_index = 0;
+    }
+    pub fn last_item(&mut self) {
+        let count = self.get_tasks_in_column(self.active_column).len();
+        self.selected_index = count.saturating_sub(1);
+    }
+
+    // --- MOUSE ---
+    /// Maps a screen position to `(column, item)`. `item` is `None` when the
+    /// point is inside a column but below its last task.
+    pub fn hit_test(&self, x: u16, y: u16) -> Option<(usize, Option<usize>)> {
+        if let Some(tab) = self
+            .tab_areas
+            .iter()
+            .position(|area| area.contains((x, y).into()))
+        {
+            return Some((tab, None));
+        }
+        let col = self
+            .column_areas
+            .iter()
+            .position(|area| area.contains((x, y).into()))?;
+        let area = self.column_areas[col];
+        if y <= area.y || y >= area.bottom() - 1 {
+            return Some((col, None)); // On the border
+        }
+        let offset = self.list_states[col].offset();
+        let mut top = area.y + 1;
+        for (index, &height) in self.item_heights[col].iter().enumerate().skip(offset) {
+            if y < top + height {
+                return Some((col, Some(index)));
+            }
+            top += height;
+        }
+        Some((col, None))
+    }
+
+    pub fn select(&mut self, col: usize, index: Option<usize>) {
+        self.active_column = col;
+        self.selected_index = index.unwrap_or(0);
+    }
+
+    /// Scrolls a column by `delta` rows, keeping the selection on screen.
+    pub fn scroll_column(&mut self, col: usize, delta: isize) {
+        let count = self.get_tasks_in_column(col).len();
+        let rows = self.column_areas[col].height.saturating_sub(2);
+        let heights = &self.item_heights[col];
+        let state = &mut self.list_states[col];
+        let offset = state
+            .offset()
+            .saturating_add_signed(delta)
+            .min(max_offset(heights, rows));
+        *state.offset_mut() = offset;
+
+        let visible = items_fitting(heights, offset, rows);
+        if col == self.active_column && visible > 0 {
+            self.selected_index = self.selected_index.clamp(offset, offset + visible - 1);
+            self.selected_index = self.selected_index.min(count.saturating_sub(1));
+        }
+    }
+
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed = !self.zoomed;
+        self.flash(if self.zoomed {
+            "Zoomed in"
+        } else {
+            "Zoomed out"
+        });
+    }
+
+    pub fn cycle_layout(&mut self) {
+        self.layout = match self.layout {
+            ColumnLayout::Auto => ColumnLayout::Columns,
+            ColumnLayout::Columns => ColumnLayout::Tabs,
+            ColumnLayout::Tabs => ColumnLayout::Stack,
+            ColumnLayout::Stack => ColumnLayout::Auto,
+        };
+        self.zoomed = false;
+        self.flash(format!("Layout: {:?}", self.layout).to_lowercase());
+    }
+
+    pub fn toggle_density(&mut self) {
+        self.density = match self.density {
+            Density::Compact => Density::Cards,
+            Density::Cards => Density::Compact,
+        };
+        self.flash(format!("Density: {:?}", self.density).to_lowercase());
+    }
+
+    /// Drops the dragged task into `col` at position `target` (or at the end
+    /// of the column when `target` is `None`), changing its status if needed.
+    pub fn drop_task(&mut self, drag: Drag, col: usize, target: Option<usize>) {
+        let source = match self.column_indices(drag.column).get(drag.index) {
+            Some(&i) => i,
+            None => return,
+        };
+        if drag.column == col && target == Some(drag.index) {
+            return;
+        }
+
+        let mut task = self.tasks.remove(source);
+        let moved = task.status != column_status(col);
+        if moved {
+            task.set_status(column_status(col));
+        }
+        // The dropped card takes the target's slot, so dragging down within
+        // a column lands after the target and dragging up lands before it.
+        let column = self.column_indices(col);
+        let (insert_at, new_index) = match target.and_then(|t| column.get(t).map(|&g| (g, t))) {
+            Some((global, local)) => (global, local),
+            None => (
+                column.last().map_or(self.tasks.len(), |&g| g + 1),
+                column.len(),
+            ),
+        };
+        self.tasks.insert(insert_at, task);
+        if moved {
+            self.moved(insert_at);
+        }
+        self.select(col, Some(new_index));
+        self.save();
+    }
 
     // --- REORDERING ---
     pub fn move_task_up(&mut self) {
@@ -152,72 +911,350 @@ impl<'a> App<'a> {
 
     // --- FOOTER INPUT (Quick Add) ---
     pub fn start_adding(&mut self) {
-        self.input_mode = true;
+        self.open(Mode::QuickAdd);
         self.title_editor = TextArea::default();
         self.title_editor.set_cursor_line_style(Style::default()); // Single line feel
+        self.quick_add_template = None;
     }
     pub fn cancel_input(&mut self) {
-        self.input_mode = false;
+        self.close(Mode::QuickAdd);
     }
 
-    pub fn submit_input(&mut self) {
-        // Join lines to ensure single line title
-        let title = self.title_editor.lines().join(" ");
+    // --- TEMPLATES ---
+    pub fn open_templates(&mut self) {
+        if self.templates.is_empty() {
+            self.flash("No templates (add some under \"templates\" in the config)");
+            return;
+        }
+        self.template_picker = Some(0);
+        self.open(Mode::Templates);
+    }
+    pub fn close_templates(&mut self) {
+        self.template_picker = None;
+        self.close(Mode::Templates);
+    }
+    pub fn next_template(&mut self) {
+        if let Some(i) = &mut self.template_picker
+            && *i + 1 < self.templates.len()
+        {
+            *i += 1;
+        }
+    }
+    pub fn prev_template(&mut self) {
+        if let Some(i) = &mut self.template_picker {
+            *i = i.saturating_sub(1);
+        }
+    }
+    /// Switches to quick add with the template's title prefix typed in.
+    pub fn pick_template(&mut self) {
+        let Some(i) = self.template_picker else {
+            return;
+        };
+        self.close_templates();
+        self.start_adding();
+        self.title_editor = single_line(self.templates[i].title.clone());
+        self.quick_add_template = Some(i);
+    }
 
-        if title.trim().is_empty() {
+    /// What the quick add input will create, for the live preview.
+    pub fn parse_input(&self) -> quick_add::Parsed {
+        quick_add::parse(
+            &self.title_editor.lines().join(" "),
+            Local::now().date_naive(),
+        )
+    }
+
+    /// Adds the task to the active column at the cursor, taking the
+    /// selected task's place.
+    pub fn submit_input(&mut self) {
+        let input = self.title_editor.lines().join(" ");
+        if input.trim().is_empty() {
             self.cancel_input();
             return;
         }
+        let parsed = self.parse_input();
+        if parsed.title.is_empty() {
+            self.flash_error(
+                "The task needs a title besides #tags, !priority, @name and due:, est:, repeat:, sprint: or parent: tokens",
+            );
+            return;
+        }
+        if let Some(id) = parsed.parent
+            && !self.tasks.iter().any(|t| t.id == id)
+        {
+            self.flash_error(format!("No task #{} to add it below", id));
+            return;
+        }
 
-        self.tasks.push(Task {
-            title: title.trim().to_string(),
-            description: String::new(),
-            status: Status::Todo,
-        });
+        let status = column_status(self.active_column);
+        let mut task = Task::new("", status.clone());
+        parsed.apply(&mut task);
+        if let Some(template) = self.quick_add_template.map(|i| &self.templates[i]) {
+            template.fill(&mut task);
+        }
+        self.flash(format!("Added '{}' to {}", task.title, status.label()));
+        match self.get_selected_global_index() {
+            Some(idx) => self.tasks.insert(idx, task),
+            None => {
+                self.tasks.push(task);
+                let shown = self.get_tasks_in_column(self.active_column).len();
+                self.selected_index = shown.saturating_sub(1);
+            }
+        }
         self.save();
-        self.input_mode = false;
+        self.close(Mode::QuickAdd);
     }
 
     // --- DELETE ---
+    /// Epics ask what happens to their children.
     pub fn prompt_delete(&mut self) {
-        if !self.get_tasks_in_column(self.active_column).is_empty() {
-            self.delete_mode = true;
+        let Some(task) = self.current_task() else {
+            return;
+        };
+        if epics::children(&self.tasks, task.id).is_empty() {
+            self.open(Mode::Delete);
+        } else {
+            self.open(Mode::DeleteEpic);
         }
     }
     pub fn cancel_delete(&mut self) {
-        self.delete_mode = false;
+        self.close(Mode::Delete);
+        self.close(Mode::DeleteEpic);
     }
-    pub fn confirm_delete(&mut self) {
+    /// Deletes the selected task and, with `children`, everything below
+    /// it. Otherwise its children move up to its own parent.
+    pub fn confirm_delete(&mut self, children: bool) {
         if let Some(idx) = self.get_selected_global_index() {
-            self.tasks.remove(idx);
-            self.save();
-            if self.selected_index > 0 {
-                self.selected_index -= 1;
+            let below = epics::descendants(&self.tasks, idx);
+            let (id, parent) = (self.tasks[idx].id, self.tasks[idx].parent);
+            let message = if below.is_empty() {
+                format!("Deleted '{}'", self.tasks[idx].title)
+            } else if children {
+                format!(
+                    "Deleted '{}' with the tasks below it: {}",
+                    self.tasks[idx].title,
+                    below.len()
+                )
+            } else {
+                let moved = epics::children(&self.tasks, id);
+                for &child in &moved {
+                    self.tasks[child].parent = parent;
+                    self.tasks[child].touch();
+                }
+                format!(
+                    "Deleted '{}'; child tasks moved up a level: {}",
+                    self.tasks[idx].title,
+                    moved.len()
+                )
+            };
+            let mut removed = vec![idx];
+            if children {
+                removed.extend(below);
+            }
+            removed.sort_unstable();
+            for &i in removed.iter().rev() {
+                self.tasks.remove(i);
             }
+            self.flash(message);
+            self.save();
+            let shown = self.get_tasks_in_column(self.active_column).len();
+            self.selected_index = self
+                .selected_index
+                .saturating_sub(1)
+                .min(shown.saturating_sub(1));
         }
-        self.delete_mode = false;
+        // The view it was deleted from shows a task that is gone
+        self.close(Mode::Delete);
+        self.close(Mode::DeleteEpic);
+        self.close(Mode::View);
     }
     pub fn move_current_task(&mut self) {
         if let Some(idx) = self.get_selected_global_index() {
             let task = &mut self.tasks[idx];
-            task.status = match task.status {
+            task.set_status(match task.status {
                 Status::Todo => Status::Doing,
                 Status::Doing => Status::Done,
                 Status::Done => Status::Todo,
-            };
+            });
+            self.moved(idx);
             self.save();
         }
     }
+    /// Reports a status change and, when a repeating task was finished,
+    /// adds its next occurrence to TODO. The finished one stops repeating
+    /// so moving it back and forth does not add more.
+    fn moved(&mut self, idx: usize) {
+        let task = &mut self.tasks[idx];
+        let mut message = format!("Moved '{}' to {}", task.title, task.status.label());
+        if task.status == Status::Done && timesheet::stop(task, Utc::now()) {
+            message.push_str(", timer stopped");
+        }
+        if task.status == Status::Done
+            && let Some(rule) = task.repeat.take()
+        {
+            let next = next_occurrence(task, rule);
+            if let Some(due) = next.due {
+                message.push_str(&format!(", next due {}", due.format("%a %b %d")));
+            }
+            self.tasks.push(next);
+        }
+        self.flash(message);
+    }
+
+    // --- TIME TRACKING ---
+    /// Stops the selected task's timer, or starts it (stopping any other)
+    /// and moves the task to DOING.
+    pub fn toggle_timer(&mut self) {
+        let Some(idx) = self.get_selected_global_index() else {
+            return;
+        };
+        let now = Utc::now();
+        if timesheet::stop(&mut self.tasks[idx], now) {
+            let task = &self.tasks[idx];
+            let message = format!(
+                "Stopped '{}' at {}",
+                task.title,
+                timesheet::hours(timesheet::total(task, now))
+            );
+            self.flash(message);
+            self.save();
+            return;
+        }
+        let task = &mut self.tasks[idx];
+        task.time_entries.push(TimeEntry {
+            start: now,
+            end: None,
+        });
+        let mut message = format!("Timing '{}'", task.title);
+        if task.status != Status::Doing {
+            task.set_status(Status::Doing);
+            message.push_str(" in DOING");
+        }
+        let other = (0..self.tasks.len())
+            .find(|&i| i != idx && self.tasks[i].time_entries.iter().any(|e| e.end.is_none()));
+        if let Some(other) = other {
+            timesheet::stop(&mut self.tasks[other], now);
+            message.push_str(&format!(", stopped '{}'", self.tasks[other].title));
+        }
+        self.flash(message);
+        self.save();
+    }
+    /// The task being timed and how long the timer has run.
+    pub fn running_timer(&self) -> Option<(&Task, chrono::Duration)> {
+        timesheet::running(&self.tasks).map(|(i, start)| (&self.tasks[i], Utc::now() - start))
+    }
 
     // --- VIEW MODE ---
     pub fn open_view_mode(&mut self) {
         if self.get_tasks_in_column(self.active_column).is_empty() {
             return;
         }
-        self.view_mode = true;
+        self.open(Mode::View);
+        self.view_checkbox = 0;
+        self.view_child = 0;
+        self.view_scroll = 0;
     }
     pub fn close_view_mode(&mut self) {
-        self.view_mode = false;
+        self.close(Mode::View);
+    }
+    /// Source line of the highlighted checkbox, if the task has any.
+    pub fn selected_checkbox_line(&self) -> Option<usize> {
+        let (_, description) = self.get_current_task_info();
+        markdown::checkbox_lines(&description)
+            .get(self.view_checkbox)
+            .copied()
+    }
+    pub fn next_checkbox(&mut self) {
+        let (_, description) = self.get_current_task_info();
+        let count = markdown::checkbox_lines(&description).len();
+        if count > 0 {
+            self.view_checkbox = (self.view_checkbox + 1) % count;
+            self.view_follow_checkbox = true;
+        }
+    }
+    pub fn prev_checkbox(&mut self) {
+        let (_, description) = self.get_current_task_info();
+        let count = markdown::checkbox_lines(&description).len();
+        if count > 0 {
+            self.view_checkbox = (self.view_checkbox + count - 1) % count;
+            self.view_follow_checkbox = true;
+        }
+    }
+    /// Highlights the next (`delta` 1) or previous child of the task.
+    pub fn cycle_child(&mut self, delta: isize) {
+        let Some(task) = self.current_task() else {
+            return;
+        };
+        let count = epics::children(&self.tasks, task.id).len() as isize;
+        if count > 0 {
+            self.view_child = (self.view_child as isize + delta).rem_euclid(count) as usize;
+        }
+    }
+    /// Shows the highlighted child in the view.
+    pub fn open_child(&mut self) {
+        let Some(task) = self.current_task() else {
+            return;
+        };
+        match epics::children(&self.tasks, task.id).get(self.view_child) {
+            Some(&idx) => self.show_in_view(idx),
+            None => self.flash("The task has no children"),
+        }
+    }
+    /// Shows the task's epic in the view.
+    pub fn open_parent(&mut self) {
+        let Some(task) = self.current_task() else {
+            return;
+        };
+        match epics::parent(&self.tasks, task) {
+            Some(idx) => self.show_in_view(idx),
+            None => self.flash("The task is not part of an epic"),
+        }
+    }
+    fn show_in_view(&mut self, idx: usize) {
+        self.reveal(idx);
+        self.view_checkbox = 0;
+        self.view_child = 0;
+        self.view_scroll = 0;
+    }
+    /// Selects the task at `idx`, clearing a filter that hides it.
+    fn reveal(&mut self, idx: usize) {
+        if !matches_filter(&self.tasks[idx], &self.filter) {
+            self.clear_filter();
+        }
+        let col = self.tasks[idx].status.column();
+        let index = self.column_indices(col).iter().position(|&i| i == idx);
+        self.select(col, index);
+    }
+    pub fn scroll_view(&mut self, delta: isize) {
+        self.view_scroll =
+            (self.view_scroll as isize + delta).clamp(0, self.view_scroll_max as isize) as u16;
+    }
+    /// One page minus a line of overlap.
+    pub fn scroll_view_page(&mut self, pages: isize) {
+        let page = self.view_height.saturating_sub(1).max(1) as isize;
+        self.scroll_view(pages * page);
+    }
+    pub fn scroll_view_home(&mut self) {
+        self.view_scroll = 0;
+    }
+    pub fn scroll_view_end(&mut self) {
+        self.view_scroll = self.view_scroll_max;
+    }
+    pub fn toggle_checkbox(&mut self) {
+        if let (Some(line), Some(idx)) = (
+            self.selected_checkbox_line(),
+            self.get_selected_global_index(),
+        ) {
+            let task = &mut self.tasks[idx];
+            task.description = markdown::toggle_checkbox(&task.description, line);
+            task.touch();
+            self.save();
+        }
+    }
+    pub fn current_task(&self) -> Option<&Task> {
+        self.get_tasks_in_column(self.active_column)
+            .get(self.selected_index)
+            .copied()
     }
     pub fn get_current_task_info(&self) -> (String, String) {
         let tasks_in_col = self.get_tasks_in_column(self.active_column);
@@ -245,11 +1282,30 @@ impl<'a> App<'a> {
             self.description_editor = TextArea::new(lines);
 
             self.edit_focus = EditFocus::Title;
-            self.edit_mode = true;
+            self.open(Mode::Edit);
         }
     }
     pub fn close_edit_mode(&mut self) {
-        self.edit_mode = false;
+        self.close(Mode::Discard);
+        self.close(Mode::Edit);
+    }
+    /// Esc from the edit modal: asks first when something was changed.
+    pub fn cancel_edit(&mut self) {
+        if self.edit_is_modified() {
+            self.open(Mode::Discard);
+        } else {
+            self.close_edit_mode();
+        }
+    }
+    pub fn keep_editing(&mut self) {
+        self.close(Mode::Discard);
+    }
+    fn edit_is_modified(&self) -> bool {
+        let Some(task) = self.current_task() else {
+            return false;
+        };
+        self.title_editor.lines().join(" ").trim() != task.title
+            || self.description_editor.lines() != task.description.lines().collect::<Vec<_>>()
     }
     pub fn toggle_edit_focus(&mut self) {
         self.edit_focus = match self.edit_focus {
@@ -268,8 +1324,562 @@ impl<'a> App<'a> {
         if let Some(idx) = self.get_selected_global_index() {
             self.tasks[idx].title = new_title.trim().to_string();
             self.tasks[idx].description = new_desc;
+            self.tasks[idx].touch();
+            self.flash("Saved");
             self.save();
         }
-        self.edit_mode = false;
+        self.close(Mode::Edit);
+    }
+
+    // --- EXTERNAL EDITOR ---
+    /// Markdown for the selected task. In edit mode the unsaved buffers are
+    /// used so nothing typed so far is lost.
+    pub fn external_edit_text(&self) -> Option<String> {
+        let mut task = self.tasks[self.get_selected_global_index()?].clone();
+        if self.is_open(Mode::Edit) {
+            task.title = self.title_editor.lines().join(" ");
+            task.description = self.description_editor.lines().join("\n");
+        }
+        Some(external_editor::to_markdown(&task))
+    }
+
+    /// Parses the editor output into the selected task and saves it.
+    /// The selection follows the task if its status changed.
+    pub fn apply_external_edit(&mut self, text: &str) -> Result<()> {
+        let idx = self
+            .get_selected_global_index()
+            .ok_or_else(|| anyhow!("The task no longer exists"))?;
+        let mut task = self.tasks[idx].clone();
+        external_editor::apply_markdown(&mut task, text)?;
+        if let Some(parent) = task.parent.filter(|&p| Some(p) != self.tasks[idx].parent) {
+            epics::check_parent(&self.tasks, task.id, parent)?;
+        }
+        task.touch();
+        let status_changed = task.status != self.tasks[idx].status;
+        self.tasks[idx] = task;
+        // Stops the timer and repeats the task like any other move
+        if status_changed {
+            self.moved(idx);
+        } else {
+            self.flash("Saved");
+        }
+        self.save();
+
+        let col = self.tasks[idx].status.column();
+        let index = self.column_indices(col).iter().position(|&i| i == idx);
+        self.select(col, index);
+        self.close(Mode::Edit);
+        Ok(())
+    }
+
+    /// Keeps the unparsable editor output so it can be edited again.
+    pub fn external_edit_failed(&mut self, message: String, text: String) {
+        self.external_error = Some((message, text));
+        self.open(Mode::EditorError);
+    }
+    /// Text to retry with, closing the error popup.
+    pub fn take_external_error(&mut self) -> Option<String> {
+        self.close(Mode::EditorError);
+        self.external_error.take().map(|(_, text)| text)
+    }
+
+    // --- COMMAND LINE ---
+    pub fn open_palette(&mut self) {
+        self.palette = Some(Palette {
+            editor: single_line(String::new()),
+            history_pos: None,
+            completion: None,
+        });
+        self.open(Mode::Command);
+    }
+    pub fn close_palette(&mut self) {
+        self.palette = None;
+        self.close(Mode::Command);
+    }
+    /// Completes the last word, cycling through the candidates on repeated
+    /// presses. A single candidate is accepted with a trailing space.
+    pub fn complete_palette(&mut self) {
+        let Some(p) = &mut self.palette else {
+            return;
+        };
+        let line = p.editor.lines().join(" ");
+        let completion = match p.completion.take() {
+            Some(mut c) => {
+                c.index = (c.index + 1) % c.candidates.len();
+                c
+            }
+            None => {
+                let mut boards: Vec<String> = self.boards.keys().cloned().collect();
+                boards.sort();
+                let (start, candidates) = palette::complete(&line, &self.tasks, &boards);
+                if candidates.is_empty() {
+                    return;
+                }
+                Completion {
+                    start,
+                    candidates,
+                    index: 0,
+                }
+            }
+        };
+        let mut text = format!(
+            "{}{}",
+            &line[..completion.start],
+            completion.candidates[completion.index]
+        );
+        if completion.candidates.len() == 1 {
+            text.push(' ');
+        } else {
+            p.completion = Some(completion);
+        }
+        p.editor = single_line(text);
+    }
+    /// Up (`older`) and Down step through earlier command lines.
+    pub fn palette_history(&mut self, older: bool) {
+        let Some(p) = &mut self.palette else {
+            return;
+        };
+        let Some(last) = self.history.len().checked_sub(1) else {
+            return;
+        };
+        p.history_pos = match (p.history_pos, older) {
+            (None, true) => Some(last),
+            (None, false) => return,
+            (Some(pos), true) => Some(pos.saturating_sub(1)),
+            (Some(pos), false) if pos < last => Some(pos + 1),
+            (Some(_), false) => None,
+        };
+        let text = p
+            .history_pos
+            .map(|pos| self.history[pos].clone())
+            .unwrap_or_default();
+        p.editor = single_line(text);
+        p.completion = None;
+    }
+    /// Typing ends any completion or history browsing.
+    pub fn palette_edited(&mut self) {
+        if let Some(p) = &mut self.palette {
+            p.completion = None;
+            p.history_pos = None;
+        }
+    }
+    pub fn run_palette(&mut self) {
+        let Some(p) = self.palette.take() else {
+            return;
+        };
+        self.close(Mode::Command);
+        let line = p.editor.lines().join(" ").trim().to_string();
+        if line.is_empty() {
+            return;
+        }
+        let path = palette::history_path(&self.file_path);
+        if let Err(e) = palette::record(&mut self.history, &line, &path) {
+            self.flash_error(format!("{:#}", e));
+        }
+        if let Err(e) = palette::parse(&line).and_then(|cmd| self.run_palette_command(cmd)) {
+            self.flash_error(format!("{:#}", e));
+        }
+    }
+    fn run_palette_command(&mut self, command: PaletteCommand) -> Result<()> {
+        match command {
+            PaletteCommand::Move { status, task } => {
+                let idx = self.palette_target(task)?;
+                if self.tasks[idx].status == status {
+                    self.flash(format!(
+                        "'{}' is already in {}",
+                        self.tasks[idx].title,
+                        status.label()
+                    ));
+                    return Ok(());
+                }
+                self.tasks[idx].set_status(status);
+                self.moved(idx);
+            }
+            PaletteCommand::Tag { add, remove, task } => {
+                let idx = self.palette_target(task)?;
+                let task = &mut self.tasks[idx];
+                task.tags
+                    .retain(|t| !remove.iter().any(|r| r.trim_start_matches('#') == t));
+                for tag in add {
+                    let tag = tag.trim_start_matches('#');
+                    if !tag.is_empty() && !task.tags.iter().any(|t| t == tag) {
+                        task.tags.push(tag.to_string());
+                    }
+                }
+                task.touch();
+                let tags: Vec<String> = task.tags.iter().map(|t| format!("#{}", t)).collect();
+                let message = match tags.is_empty() {
+                    true => format!("'{}' has no tags", task.title),
+                    false => format!("Tagged '{}' {}", task.title, tags.join(" ")),
+                };
+                self.flash(message);
+            }
+            PaletteCommand::Sort(key) => {
+                // Sorted tasks take the slots the column had in the list
+                let status = column_status(self.active_column);
+                let slots: Vec<usize> = (0..self.tasks.len())
+                    .filter(|&i| self.tasks[i].status == status)
+                    .collect();
+                let mut sorted: Vec<Task> = slots.iter().map(|&i| self.tasks[i].clone()).collect();
+                sorted.sort_by(|a, b| key.compare(a, b));
+                for (slot, task) in slots.into_iter().zip(sorted) {
+                    self.tasks[slot] = task;
+                }
+                self.selected_index = 0;
+                self.flash(format!("Sorted {} by {}", status.label(), key.name()));
+            }
+            PaletteCommand::Board(None) => {
+                self.clear_filter();
+                self.flash("Showing the whole board");
+                return Ok(());
+            }
+            PaletteCommand::Board(Some(name)) => {
+                let Some(filter) = self.boards.get(&name) else {
+                    let mut names: Vec<&str> = self.boards.keys().map(|n| n.as_str()).collect();
+                    names.sort();
+                    if names.is_empty() {
+                        bail!(
+                            "No board '{}' (save one under \"boards\" in the config)",
+                            name
+                        );
+                    }
+                    bail!("No board '{}' (saved: {})", name, names.join(", "));
+                };
+                self.filter = filter.clone();
+                self.selected_index = 0;
+                self.flash(format!("Showing board '{}'", name));
+                return Ok(());
+            }
+            PaletteCommand::Export { format, path } => {
+                let text = export::export(&self.tasks, format, &Filter::default())?;
+                std::fs::write(&path, text).with_context(|| format!("Cannot write {}", path))?;
+                self.flash(format!("Exported to {}", path));
+                return Ok(());
+            }
+            PaletteCommand::Archive(status) => self.archive(status)?,
+            PaletteCommand::Sprint { name, task } => {
+                let idx = self.palette_target(task)?;
+                let name = match name.as_deref() {
+                    Some("-") => None,
+                    Some(name) => Some(name.to_string()),
+                    None => match sprint::active(&self.board.sprints) {
+                        Some(sprint) => Some(sprint.name.clone()),
+                        None => bail!("No sprint is running (name one: sprint s12)"),
+                    },
+                };
+                let task = &mut self.tasks[idx];
+                let message = match &name {
+                    Some(name) => format!("Planned '{}' into sprint '{}'", task.title, name),
+                    None => format!("Took '{}' out of its sprint", task.title),
+                };
+                task.sprint = name;
+                task.touch();
+                self.flash(message);
+            }
+            PaletteCommand::Parent { parent, task } => {
+                let idx = self.palette_target(task)?;
+                let message = match parent {
+                    Some(parent) => {
+                        epics::check_parent(&self.tasks, self.tasks[idx].id, parent)?;
+                        let epic = self.tasks.iter().find(|t| t.id == parent);
+                        let epic = epic.map(|t| t.title.as_str()).unwrap_or_default();
+                        format!("Put '{}' below '{}'", self.tasks[idx].title, epic)
+                    }
+                    None => format!("'{}' is top-level now", self.tasks[idx].title),
+                };
+                let task = &mut self.tasks[idx];
+                task.parent = parent;
+                task.touch();
+                self.flash(message);
+            }
+        }
+        self.save();
+        Ok(())
+    }
+    /// The task with ID `id`, or the selected one.
+    fn palette_target(&self, id: Option<u64>) -> Result<usize> {
+        match id {
+            Some(id) => self
+                .tasks
+                .iter()
+                .position(|t| t.id == id)
+                .ok_or_else(|| anyhow!("No task #{}", id)),
+            None => self
+                .get_selected_global_index()
+                .ok_or_else(|| anyhow!("No task selected")),
+        }
+    }
+    /// Moves every task in `status` to the archive file beside the board.
+    fn archive(&mut self, status: Status) -> Result<()> {
+        let archived: Vec<Task> = self
+            .tasks
+            .iter()
+            .filter(|t| t.status == status)
+            .cloned()
+            .collect();
+        if archived.is_empty() {
+            bail!("No {} tasks to archive", status.label());
+        }
+        let count = archived.len();
+        let path = io::archive_path(&self.file_path);
+        let mut doc = io::load(&path).context("Archiving failed")?;
+        doc.tasks.extend(archived);
+        io::save(&path, &doc.envelope, &doc.board, &doc.tasks).context("Archiving failed")?;
+
+        self.tasks.retain(|t| t.status != status);
+        self.selected_index = 0;
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        self.flash(format!(
+            "Archived {} {} tasks to {}",
+            count,
+            status.label(),
+            file
+        ));
+        Ok(())
+    }
+
+    // --- SCAN ---
+    /// Imports TODO/FIXME/HACK comments from the repository.
+    pub fn scan(&mut self) {
+        match scan::run(&mut self.tasks) {
+            Ok(report) => {
+                self.flash(report.to_string());
+                self.save();
+            }
+            Err(e) => self.flash_error(format!("{:#}", e)),
+        }
+    }
+
+    // --- LOCATIONS ---
+    /// Lists the `path:line` references of the selected task, re-resolved
+    /// against the current checkout. A moved scan source is saved back.
+    pub fn open_locations(&mut self) {
+        let Some(idx) = self.get_selected_global_index() else {
+            return;
+        };
+        let root = git::repo_root()
+            .or_else(|_| std::env::current_dir())
+            .unwrap_or_default();
+        let items: Vec<Location> = locations::of_task(&self.tasks[idx], &root)
+            .iter()
+            .map(|l| locations::resolve(&root, l))
+            .collect();
+        if items.is_empty() {
+            self.flash("No linked locations (add `path:line` to the description)");
+            return;
+        }
+
+        if let (Some(source), Some(first)) = (&mut self.tasks[idx].source, items.first())
+            && (source.path != first.path || source.line != first.line)
+        {
+            source.path = first.path.clone();
+            source.line = first.line;
+            source.commit = first.commit.clone();
+            self.save();
+        }
+        self.location_picker = Some(LocationPicker {
+            root,
+            items,
+            selected: 0,
+        });
+        self.open(Mode::Locations);
+    }
+    pub fn close_locations(&mut self) {
+        self.location_picker = None;
+        self.close(Mode::Locations);
+    }
+    pub fn next_location(&mut self) {
+        if let Some(picker) = &mut self.location_picker
+            && picker.selected + 1 < picker.items.len()
+        {
+            picker.selected += 1;
+        }
+    }
+    pub fn prev_location(&mut self) {
+        if let Some(picker) = &mut self.location_picker {
+            picker.selected = picker.selected.saturating_sub(1);
+        }
+    }
+    /// Absolute path and line of the highlighted location.
+    pub fn selected_location(&self) -> Option<(PathBuf, usize)> {
+        let picker = self.location_picker.as_ref()?;
+        let location = picker.items.get(picker.selected)?;
+        Some((picker.root.join(&location.path), location.line))
+    }
+
+    // --- EXPORT ---
+    pub fn open_export(&mut self) {
+        let format = Format::Markdown;
+        let mut path = TextArea::new(vec![format!("kanban.{}", format.extension())]);
+        path.set_cursor_line_style(Style::default());
+        path.move_cursor(tui_textarea::CursorMove::End);
+        self.export_dialog = Some(ExportDialog { format, path });
+        self.open(Mode::Export);
+    }
+    pub fn close_export(&mut self) {
+        self.export_dialog = None;
+        self.close(Mode::Export);
+    }
+    /// Cycles the format, updating the extension of the default file name.
+    pub fn next_export_format(&mut self) {
+        let Some(dialog) = &mut self.export_dialog else {
+            return;
+        };
+        let old = dialog.format;
+        let pos = Format::ALL.iter().position(|f| *f == old).unwrap_or(0);
+        dialog.format = Format::ALL[(pos + 1) % Format::ALL.len()];
+
+        let path = dialog.path.lines().join("");
+        if let Some(stem) = path.strip_suffix(&format!(".{}", old.extension())) {
+            dialog.path = TextArea::new(vec![format!("{}.{}", stem, dialog.format.extension())]);
+            dialog.path.set_cursor_line_style(Style::default());
+            dialog.path.move_cursor(tui_textarea::CursorMove::End);
+        }
+    }
+    fn render_export(&self) -> Option<(String, Result<String>)> {
+        let dialog = self.export_dialog.as_ref()?;
+        let path = dialog.path.lines().join("").trim().to_string();
+        Some((
+            path,
+            export::export(&self.tasks, dialog.format, &Filter::default()),
+        ))
+    }
+    pub fn export_to_file(&mut self) {
+        let Some((path, output)) = self.render_export() else {
+            return;
+        };
+        if path.is_empty() {
+            return;
+        }
+        let result = output.and_then(|text| Ok(std::fs::write(&path, text)?));
+        match result {
+            Ok(()) => self.flash(format!("Exported to {}", path)),
+            Err(e) => self.flash_error(format!("Export failed: {:#}", e)),
+        }
+        self.close_export();
+    }
+    pub fn export_to_clipboard(&mut self) {
+        let Some((_, output)) = self.render_export() else {
+            return;
+        };
+        let result = output.and_then(|text| Ok(clipboard::copy(&text)?));
+        match result {
+            Ok(()) => self.flash("Copied to clipboard"),
+            Err(e) => self.flash_error(format!("Export failed: {:#}", e)),
+        }
+        self.close_export();
+    }
+}
+
+/// The next copy of a repeating task finished today, with its checklist
+/// unchecked.
+fn next_occurrence(task: &Task, rule: Recurrence) -> Task {
+    let mut next = Task::new(task.title.clone(), Status::Todo);
+    next.description = markdown::uncheck_all(&task.description);
+    next.tags = task.tags.clone();
+    next.priority = task.priority;
+    next.assignee = task.assignee.clone();
+    next.estimate = task.estimate;
+    next.due = rule.next_due(task.due, Local::now().date_naive());
+    next.repeat = Some(rule);
+    next.parent = task.parent;
+    next
+}
+
+/// A one-line `TextArea` with the cursor at the end of `text`.
+fn single_line<'a>(text: String) -> TextArea<'a> {
+    let mut editor = TextArea::new(vec![text]);
+    editor.set_cursor_line_style(Style::default());
+    editor.move_cursor(tui_textarea::CursorMove::End);
+    editor
+}
+
+/// `p1`, `P2`, `3`, `high`, `medium`, `low` -> 1..=9
+pub fn parse_priority(s: &str) -> Result<u8> {
+    let s = s.trim().to_ascii_lowercase();
+    let n = match s.as_str() {
+        "high" | "h" => 1,
+        "medium" | "m" => 2,
+        "low" | "l" => 3,
+        _ => s.trim_start_matches('p').parse().unwrap_or(0),
+    };
+    if !(1..=9).contains(&n) {
+        bail!(
+            "Invalid priority '{}' (expected p1-p9, high, medium or low)",
+            s
+        );
+    }
+    Ok(n)
+}
+
+/// The last second of `day` in local time.
+pub fn end_of_day(day: NaiveDate) -> DateTime<Utc> {
+    (day + chrono::Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| Local.from_local_datetime(&t).earliest())
+        .map_or_else(Utc::now, |t| {
+            t.with_timezone(&Utc) - chrono::Duration::seconds(1)
+        })
+}
+
+/// `bug, #ui  docs` -> `["bug", "ui", "docs"]`
+pub fn parse_tags(s: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in s.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = tag.trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Number of items from `offset` on that fit entirely in `rows`.
+pub fn items_fitting(heights: &[u16], offset: usize, rows: u16) -> usize {
+    let mut used = 0;
+    heights
+        .iter()
+        .skip(offset)
+        .take_while(|&&h| {
+            used += h;
+            used <= rows
+        })
+        .count()
+}
+
+/// Smallest offset that still shows the last item.
+pub fn max_offset(heights: &[u16], rows: u16) -> usize {
+    let mut used = 0;
+    let fitting = heights
+        .iter()
+        .rev()
+        .take_while(|&&h| {
+            used += h;
+            used <= rows
+        })
+        .count();
+    heights.len() - fitting.max(1).min(heights.len())
+}
+
+/// Every word of `filter` must match: `#tag` a tag, anything else the
+/// title or description (case-insensitive).
+pub fn matches_filter(task: &Task, filter: &str) -> bool {
+    filter.split_whitespace().all(|word| {
+        let word = word.to_lowercase();
+        match word.strip_prefix('#') {
+            Some(tag) => task.tags.iter().any(|t| t.to_lowercase() == tag),
+            None => {
+                task.title.to_lowercase().contains(&word)
+                    || task.description.to_lowercase().contains(&word)
+            }
+        }
+    })
+}
+
+fn column_status(col_idx: usize) -> Status {
+    match col_idx {
+        0 => Status::Todo,
+        1 => Status::Doing,
+        _ => Status::Done,
     }
 }
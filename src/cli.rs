@@ -0,0 +1,414 @@
+//! Subcommands (`git kanban <command>`). Without a command the TUI starts.
+
+use crate::app::{Status, Task};
+use crate::config::Config;
+use crate::export::{self, Filter, Format};
+use crate::forecast::{self, Forecast};
+use crate::importer::{self, Source};
+use crate::sprint;
+use crate::templates::Template;
+use crate::timesheet::{Group, Report};
+use crate::{io, quick_add, scan};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{Duration, Local, NaiveDate, Utc};
+use std::{fs, path::PathBuf};
+
+const USAGE: &str = "\
+Usage: git-kanban [command]
+
+Without a command, opens the board.
+
+Commands:
+  add       Add a task: TITLE... [--template NAME] [--column todo|doing|done]
+            The title takes #tag !p1 @name due:fri est:3 parent:12 like
+            quick add
+  scan      Create tasks from TODO/FIXME/HACK comments in tracked files
+  export    Print the board: --format markdown|csv|html|json
+            [--column todo|doing|done]... [--tag NAME]...
+            [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--output FILE]
+  import    Add tasks from another tool's export: FILE
+            --from trello|github|taskwarrior|todotxt|csv [--dry-run]
+            Tasks whose title is already on the board are skipped
+  report    Time tracked with the timer: [--by task,tag,day]
+            [--format text|csv] [--since YYYY-MM-DD] [--until YYYY-MM-DD]
+            [--output FILE]
+  sprint    Show the running sprint's progress and burndown, or:
+            start NAME [--start YYYY-MM-DD] [--end YYYY-MM-DD | --days N]
+            close [--next NAME [--days N]]
+            Unfinished tasks of a closed sprint join the next one started
+  forecast  How many tasks will be done by a date and when N will be, at
+            50/85/95% confidence: [--items N] [--by YYYY-MM-DD]
+            [--history DAYS] (unfinished tasks, the sprint's end or two
+            weeks, and the last 90 days by default)
+  help      Show this message
+";
+
+pub fn run(data_path: &PathBuf, args: &[String]) -> Result<()> {
+    let rest = &args[1..];
+    match args[0].as_str() {
+        "add" => run_add(data_path, Args::parse(rest, &[])?),
+        "scan" => run_scan(data_path),
+        "export" => run_export(data_path, Args::parse(rest, &[])?),
+        "import" => run_import(data_path, Args::parse(rest, &["dry-run"])?),
+        "report" => run_report(data_path, Args::parse(rest, &[])?),
+        "sprint" => run_sprint(data_path, Args::parse(rest, &[])?),
+        "forecast" => run_forecast(data_path, Args::parse(rest, &[])?),
+        "help" | "-h" | "--help" => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        other => bail!("Unknown command '{}'\n\n{}", other, USAGE),
+    }
+}
+
+fn run_add(data_path: &PathBuf, mut args: Args) -> Result<()> {
+    let input = std::mem::take(&mut args.positional).join(" ");
+    args.expect_only(&["template", "column"])?;
+    let status: Status = args.value("column").unwrap_or("todo").parse()?;
+    let template = match args.value("template") {
+        Some(name) => {
+            let templates = Template::from_config(&Config::load(data_path)?)?;
+            let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+            let found = templates.iter().find(|t| t.name == name).cloned();
+            Some(found.ok_or_else(|| {
+                anyhow!(
+                    "Unknown template '{}' (available: {})",
+                    name,
+                    names.join(", ")
+                )
+            })?)
+        }
+        None => None,
+    };
+
+    let parsed = quick_add::parse(&input, Local::now().date_naive());
+    if let Some(problem) = parsed.problems.first() {
+        bail!("{}", problem);
+    }
+    if parsed.title.is_empty() && template.is_none() {
+        bail!("Missing task title\n\n{}", USAGE);
+    }
+    let mut task = Task::new("", status);
+    parsed.apply(&mut task);
+    if let Some(template) = &template {
+        task.title = template.prefixed(&task.title);
+        template.fill(&mut task);
+    }
+
+    let mut doc = io::load(data_path)?;
+    if let Some(id) = task.parent
+        && !doc.tasks.iter().any(|t| t.id == id)
+    {
+        bail!("No task #{} to add it below", id);
+    }
+    doc.tasks.push(task);
+    save(data_path, &mut doc)?;
+    let task = doc.tasks.last().context("Task was not added")?;
+    println!("Added #{} {}", task.id, task.title);
+    Ok(())
+}
+
+fn run_scan(data_path: &PathBuf) -> Result<()> {
+    let mut doc = io::load(data_path)?;
+    let report = scan::run(&mut doc.tasks)?;
+    save(data_path, &mut doc)?;
+    println!("{}", report);
+    Ok(())
+}
+
+fn run_export(data_path: &PathBuf, args: Args) -> Result<()> {
+    args.expect_only(&["format", "column", "tag", "since", "until", "output"])?;
+    let format: Format = args.value("format").unwrap_or("markdown").parse()?;
+    let filter = Filter {
+        columns: args
+            .values("column")
+            .into_iter()
+            .map(str::parse::<Status>)
+            .collect::<Result<_>>()?,
+        tags: args.values("tag").into_iter().map(String::from).collect(),
+        since: args.value("since").map(parse_date).transpose()?,
+        until: args.value("until").map(parse_date).transpose()?,
+    };
+
+    let doc = io::load(data_path)?;
+    let output = export::export(&doc.tasks, format, &filter)?;
+    match args.value("output") {
+        Some(path) => fs::write(path, output).with_context(|| format!("Cannot write {}", path)),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
+
+fn run_import(data_path: &PathBuf, mut args: Args) -> Result<()> {
+    let file = match args.positional.as_slice() {
+        [_] => args.positional.remove(0),
+        [] => bail!("Missing file to import\n\n{}", USAGE),
+        _ => bail!("Only one file can be imported at a time"),
+    };
+    args.expect_only(&["from", "dry-run"])?;
+    let source: Source = args
+        .value("from")
+        .ok_or_else(|| anyhow!("Missing --from (trello, github, taskwarrior, todotxt or csv)"))?
+        .parse()?;
+    let dry_run = args.value("dry-run").is_some();
+
+    let text = fs::read_to_string(&file).with_context(|| format!("Cannot read {}", file))?;
+    let mut doc = io::load(data_path)?;
+    let plan = importer::plan(&doc.tasks, importer::read(source, &text)?);
+
+    for task in &plan.new {
+        let mut line = format!("+ [{}] {}", task.status.name(), task.title);
+        if let Some(p) = task.priority {
+            line.push_str(&format!(" p{}", p));
+        }
+        if let Some(due) = task.due {
+            line.push_str(&format!(" due:{}", due));
+        }
+        for tag in &task.tags {
+            line.push_str(&format!(" #{}", tag));
+        }
+        println!("{}", line);
+    }
+    for task in &plan.duplicates {
+        println!("= {} (already on the board)", task.title);
+    }
+
+    let (added, skipped) = (plan.new.len(), plan.duplicates.len());
+    if dry_run {
+        println!(
+            "Dry run: {} would be added, {} duplicates skipped",
+            added, skipped
+        );
+        return Ok(());
+    }
+    doc.tasks.extend(plan.new);
+    save(data_path, &mut doc)?;
+    println!("Import: {} added, {} duplicates skipped", added, skipped);
+    Ok(())
+}
+
+fn run_report(data_path: &PathBuf, args: Args) -> Result<()> {
+    args.expect_only(&["by", "format", "since", "until", "output"])?;
+    let groups = match args.values("by") {
+        names if names.is_empty() => Group::ALL.to_vec(),
+        names => names
+            .into_iter()
+            .map(str::parse)
+            .collect::<Result<Vec<Group>>>()?,
+    };
+    let since = args.value("since").map(parse_date).transpose()?;
+    let until = args.value("until").map(parse_date).transpose()?;
+
+    let doc = io::load(data_path)?;
+    let report = Report::new(&doc.tasks, since, until, Utc::now());
+    let output = match args.value("format").unwrap_or("text") {
+        "text" => report.to_text(&groups),
+        "csv" => report.to_csv(&groups),
+        other => bail!("Unknown report format '{}' (expected text or csv)", other),
+    };
+    match args.value("output") {
+        Some(path) => fs::write(path, output).with_context(|| format!("Cannot write {}", path)),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
+
+fn run_sprint(data_path: &PathBuf, mut args: Args) -> Result<()> {
+    let unit = Config::load(data_path)?.estimate_unit;
+    let today = Local::now().date_naive();
+    let mut doc = io::load(data_path)?;
+    let positional = std::mem::take(&mut args.positional);
+    let message = match positional.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] | ["status"] => {
+            args.expect_only(&[])?;
+            let sprint = sprint::current(&doc.board.sprints).ok_or_else(|| {
+                anyhow!("No sprints yet (start one with 'git kanban sprint start NAME')")
+            })?;
+            let summary = sprint.summary(&doc.tasks);
+            let state = if sprint.closed.is_some() {
+                "closed"
+            } else {
+                "running"
+            };
+            println!("{} ({}, {})", sprint.name, sprint.dates(), state);
+            println!(
+                "  Committed {}, scope {}, completed {} ({} of {} tasks done)",
+                unit.format(summary.committed),
+                unit.format(summary.scope),
+                unit.format(summary.completed),
+                summary.done,
+                summary.tasks
+            );
+            if summary.unestimated > 0 {
+                println!("  Tasks without an estimate: {}", summary.unestimated);
+            }
+            for (day, remaining) in sprint.burndown(&doc.tasks, today) {
+                println!(
+                    "  {}  {:>6}",
+                    day.format("%a %b %d"),
+                    unit.format(remaining)
+                );
+            }
+            return Ok(());
+        }
+        ["start", name] => {
+            args.expect_only(&["start", "end", "days"])?;
+            let start = args.value("start").map(parse_date).transpose()?;
+            let start = start.unwrap_or(today);
+            let end = sprint_end(&args, start)?;
+            sprint::start(&mut doc.board, &mut doc.tasks, name, start, end, unit)?
+        }
+        ["start"] => bail!("Missing sprint name\n\n{}", USAGE),
+        ["close"] => {
+            args.expect_only(&["next", "days"])?;
+            let mut message = sprint::close(&mut doc.board, &doc.tasks, today, unit)?;
+            if let Some(next) = args.value("next") {
+                let end = sprint_end(&args, today)?;
+                let started =
+                    sprint::start(&mut doc.board, &mut doc.tasks, next, today, end, unit)?;
+                message = format!("{}\n{}", message, started);
+            }
+            message
+        }
+        _ => bail!("Unknown sprint command\n\n{}", USAGE),
+    };
+    save(data_path, &mut doc)?;
+    println!("{}", message);
+    Ok(())
+}
+
+/// `--end`, or `--days` (14 by default) counting the start day.
+fn sprint_end(args: &Args, start: NaiveDate) -> Result<NaiveDate> {
+    if let Some(end) = args.value("end") {
+        return parse_date(end);
+    }
+    let days: i64 = match args.value("days") {
+        Some(days) => days
+            .parse()
+            .ok()
+            .filter(|&d| d > 0)
+            .ok_or_else(|| anyhow!("Invalid --days '{}' (expected a number of days)", days))?,
+        None => 14,
+    };
+    Duration::try_days(days - 1)
+        .and_then(|d| start.checked_add_signed(d))
+        .ok_or_else(|| anyhow!("--days {} ends the sprint too far in the future", days))
+}
+
+fn run_forecast(data_path: &PathBuf, args: Args) -> Result<()> {
+    args.expect_only(&["items", "by", "history"])?;
+    let number = |name: &str| -> Result<Option<u32>> {
+        args.value(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| anyhow!("Invalid --{} '{}' (expected a number)", name, v))
+            })
+            .transpose()
+    };
+    let today = Local::now().date_naive();
+    let doc = io::load(data_path)?;
+    let (items, by) = forecast::defaults(&doc.board, &doc.tasks, today);
+    let items = number("items")?.unwrap_or(items);
+    let by = args.value("by").map(parse_date).transpose()?.unwrap_or(by);
+    if by <= today {
+        bail!("--by must be after today");
+    }
+    let max = forecast::MAX_DAYS as i64;
+    if (by - today).num_days() > max {
+        bail!("--by must be within {} days of today", max);
+    }
+    let days = match number("history")? {
+        Some(0) => bail!("--history must be at least one day"),
+        Some(days) if days as i64 > max => bail!("--history can be at most {} days", max),
+        Some(days) => days as i64,
+        None => forecast::HISTORY_DAYS,
+    };
+
+    let history = forecast::throughput(&doc.tasks, days, today);
+    print!(
+        "{}",
+        Forecast::new(&history, today, items, by).to_text(today)
+    );
+    Ok(())
+}
+
+fn save(data_path: &PathBuf, doc: &mut io::Document) -> Result<()> {
+    doc.board.assign_ids(&mut doc.tasks);
+    io::save(data_path, &doc.envelope, &doc.board, &doc.tasks)
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date '{}' (expected YYYY-MM-DD)", s))
+}
+
+/// `--name value`, `--name=value` and bare `--switch` flags plus
+/// positional arguments. Comma-separated values count as several.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: &[String], switches: &[&str]) -> Result<Self> {
+        let mut parsed = Args {
+            positional: Vec::new(),
+            options: Vec::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg.clone());
+                continue;
+            };
+            let (name, value) = match flag.split_once('=') {
+                Some((name, value)) => (name, value.to_string()),
+                None if switches.contains(&flag) => (flag, String::new()),
+                None => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("Missing value for --{}", flag))?;
+                    (flag, value.clone())
+                }
+            };
+            parsed.options.push((name.to_string(), value));
+        }
+        Ok(parsed)
+    }
+
+    fn expect_only(&self, names: &[&str]) -> Result<()> {
+        if let Some((name, _)) = self
+            .options
+            .iter()
+            .find(|(n, _)| !names.contains(&n.as_str()))
+        {
+            bail!("Unknown option --{}\n\n{}", name, USAGE);
+        }
+        if let Some(arg) = self.positional.first() {
+            bail!("Unexpected argument '{}'\n\n{}", arg, USAGE);
+        }
+        Ok(())
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn values(&self, name: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(n, _)| n == name)
+            .flat_map(|(_, v)| v.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+}
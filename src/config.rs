@@ -0,0 +1,125 @@
+use crate::app::{ColumnLayout, Density, EstimateUnit};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::Path, path::PathBuf};
+
+/// User settings. Loaded from the global config file and then overridden
+/// by the per-repo file that lives next to the task storage.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub keys: KeysConfig,
+    /// Active theme: a built-in one or a key of `themes`.
+    pub theme: Option<String>,
+    pub themes: HashMap<String, ThemeSpec>,
+    /// Initial task layout, toggled on the board.
+    pub density: Density,
+    /// How the three columns are arranged.
+    pub layout: ColumnLayout,
+    /// Whether estimates are story points or hours.
+    pub estimate_unit: EstimateUnit,
+    /// Task templates by name, added to or replacing the built-in ones.
+    pub templates: HashMap<String, TemplateSpec>,
+    /// Saved views of the board by name, each a filter as typed after `/`.
+    pub boards: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// Name of a built-in binding set ("default" or "vim").
+    pub preset: Option<String>,
+    /// Action name -> list of keys. Replaces the preset keys for that action.
+    pub bindings: HashMap<String, Vec<String>>,
+}
+
+/// See `templates.rs`.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TemplateSpec {
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    /// `p1`, `high`, ...
+    pub priority: Option<String>,
+    pub checklist: Vec<String>,
+}
+
+/// A user theme: a built-in base plus per-slot style overrides.
+#[derive(Deserialize, Debug, Default)]
+pub struct ThemeSpec {
+    pub extends: Option<String>,
+    #[serde(flatten)]
+    pub styles: HashMap<String, StyleSpec>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct StyleSpec {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reversed: bool,
+}
+
+impl Config {
+    /// Loads `~/.config/git-kanban/config.json`, then merges the per-repo
+    /// override on top of it. Missing files are not an error.
+    pub fn load(data_path: &Path) -> Result<Self> {
+        let mut merged = Value::Object(Default::default());
+        let mut paths = Vec::new();
+        if let Some(global) = global_config_path() {
+            paths.push(global);
+        }
+        paths.push(repo_config_path(data_path));
+
+        for path in paths {
+            if let Some(value) = read_json(&path)? {
+                merge(&mut merged, value);
+            }
+        }
+        serde_json::from_value(merged).context("Invalid config")
+    }
+}
+
+/// `$XDG_CONFIG_HOME/git-kanban/config.json`, falling back to `~/.config`.
+pub fn global_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("git-kanban").join("config.json"))
+}
+
+/// The per-repo override sits beside the data file:
+/// `.git/git-kanban.config.json` or `.kanban.config.json`.
+pub fn repo_config_path(data_path: &Path) -> PathBuf {
+    data_path.with_extension("config.json")
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Deep-merges `overlay` into `base`. Objects are merged key by key,
+/// everything else is replaced.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
@@ -0,0 +1,100 @@
+//! Minimal RFC 4180 CSV support.
+
+/// Joins fields into one CSV record (without the line break), quoting
+/// fields that contain separators, quotes or line breaks.
+pub fn write_record<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|f| {
+            let f = f.as_ref();
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Splits CSV text into records. Quoted fields may contain separators,
+/// doubled quotes and line breaks; blank lines are skipped.
+pub fn parse(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_awkward_fields() {
+        let fields = [
+            "plain",
+            "a, b",
+            "say \"hi\"",
+            "two\nlines",
+            "crlf\r\nline",
+            "",
+            "ünïcödé 漢字",
+        ];
+        let text = format!("{}\n{}\n", write_record(&fields), write_record(&["x"]));
+        assert_eq!(parse(&text), vec![fields.to_vec(), vec!["x"]]);
+    }
+
+    #[test]
+    fn quoted_newlines_stay_in_the_field() {
+        let text =
+            "title,description\r\n\"Fix\",\"line one\nline, two\"\r\nNext,\"\"\"quoted\"\"\"\r\n";
+        assert_eq!(
+            parse(text),
+            vec![
+                vec!["title", "description"],
+                vec!["Fix", "line one\nline, two"],
+                vec!["Next", "\"quoted\""],
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_bom_and_blank_lines() {
+        let text = "\u{feff}a,b\n\n,\n1,2";
+        assert_eq!(parse(text), vec![vec!["a", "b"], vec!["1", "2"]]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn unterminated_quote_keeps_the_rest() {
+        assert_eq!(parse("a,\"b\nc"), vec![vec!["a", "b\nc"]]);
+    }
+}
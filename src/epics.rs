@@ -0,0 +1,119 @@
+//! Epics: tasks broken down into child tasks (`Task::parent`). Children
+//! move through the columns on their own; the epic shows how many are done.
+//! A task whose parent is gone (deleted or archived) is top-level again.
+
+use crate::app::{Status, Task};
+use anyhow::{Result, bail};
+use std::collections::{HashMap, HashSet};
+
+/// Positions of the tasks whose parent is `id`, in column then board order.
+pub fn children(tasks: &[Task], id: u64) -> Vec<usize> {
+    if id == 0 {
+        return Vec::new();
+    }
+    let mut children: Vec<usize> = (0..tasks.len())
+        .filter(|&i| tasks[i].parent == Some(id))
+        .collect();
+    children.sort_by_key(|&i| tasks[i].status.column());
+    children
+}
+
+/// `children` of every task that has any, keyed by its ID. Built once
+/// when many tasks are looked up, e.g. per render.
+pub fn children_by_parent(tasks: &[Task]) -> HashMap<u64, Vec<usize>> {
+    let mut map: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, task) in tasks.iter().enumerate() {
+        if let Some(parent) = task.parent.filter(|&p| p != 0) {
+            map.entry(parent).or_default().push(i);
+        }
+    }
+    for children in map.values_mut() {
+        children.sort_by_key(|&i| tasks[i].status.column());
+    }
+    map
+}
+
+/// Of the `children` of a task, those done and in total, `None` for tasks
+/// without children.
+pub fn progress(tasks: &[Task], children: Option<&Vec<usize>>) -> Option<(usize, usize)> {
+    let children = children.filter(|c| !c.is_empty())?;
+    let done = children
+        .iter()
+        .filter(|&&i| tasks[i].status == Status::Done)
+        .count();
+    Some((done, children.len()))
+}
+
+/// Position of the task's parent.
+pub fn parent(tasks: &[Task], task: &Task) -> Option<usize> {
+    let id = task.parent?;
+    tasks.iter().position(|t| t.id == id)
+}
+
+/// Positions of everything below the task at `idx`, children first.
+pub fn descendants(tasks: &[Task], idx: usize) -> Vec<usize> {
+    let children = children_by_parent(tasks);
+    let mut found = Vec::new();
+    let mut seen = HashSet::from([idx]);
+    let mut queue = vec![idx];
+    while let Some(i) = queue.pop() {
+        for &child in children.get(&tasks[i].id).into_iter().flatten() {
+            if seen.insert(child) {
+                found.push(child);
+                queue.push(child);
+            }
+        }
+    }
+    found
+}
+
+/// Every task with its depth, each below its parent. Top-level tasks come
+/// in column then board order.
+pub fn tree(tasks: &[Task]) -> Vec<(usize, usize)> {
+    let ids: HashSet<u64> = tasks.iter().map(|t| t.id).collect();
+    let children = children_by_parent(tasks);
+    let mut roots: Vec<usize> = (0..tasks.len())
+        .filter(|&i| tasks[i].parent.is_none_or(|p| !ids.contains(&p)))
+        .collect();
+    roots.sort_by_key(|&i| tasks[i].status.column());
+    let mut rows = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+    while let Some((i, depth)) = stack.pop() {
+        if !seen.insert(i) {
+            continue;
+        }
+        rows.push((i, depth));
+        for &child in children.get(&tasks[i].id).into_iter().flatten().rev() {
+            stack.push((child, depth + 1));
+        }
+    }
+    // A loop of parents written by hand has no top-level task
+    for i in 0..tasks.len() {
+        if !seen.contains(&i) {
+            rows.push((i, 0));
+        }
+    }
+    rows
+}
+
+/// Checks that the task `id` can be put below task `parent`.
+pub fn check_parent(tasks: &[Task], id: u64, parent: u64) -> Result<()> {
+    if parent == id {
+        bail!("A task cannot be its own parent");
+    }
+    let Some(mut above) = tasks.iter().find(|t| t.id == parent) else {
+        bail!("No task #{}", parent);
+    };
+    // Bounded in case the parents already loop
+    for _ in 0..tasks.len() {
+        let Some(next) = self::parent(tasks, above) else {
+            break;
+        };
+        above = &tasks[next];
+        if above.id == id {
+            bail!("#{} is already below #{}", parent, id);
+        }
+    }
+    Ok(())
+}
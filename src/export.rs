@@ -0,0 +1,261 @@
+//! Renders the board as Markdown, CSV, HTML or JSON.
+
+use crate::app::{Status, Task};
+use crate::csv;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::str::FromStr;
+
+const COLUMNS: [(Status, &str); 3] = [
+    (Status::Todo, "TODO"),
+    (Status::Doing, "DOING"),
+    (Status::Done, "DONE"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Markdown,
+    Csv,
+    Html,
+    Json,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Markdown, Format::Csv, Format::Html, Format::Json];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Markdown => "markdown",
+            Format::Csv => "csv",
+            Format::Html => "html",
+            Format::Json => "json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Csv => "csv",
+            Format::Html => "html",
+            Format::Json => "json",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "html" => Ok(Format::Html),
+            "json" => Ok(Format::Json),
+            _ => bail!(
+                "Unknown format '{}' (expected markdown, csv, html or json)",
+                s
+            ),
+        }
+    }
+}
+
+/// Which tasks to export. Empty lists match everything; dates are inclusive
+/// and compared with the task's last update in local time.
+#[derive(Default, Debug)]
+pub struct Filter {
+    pub columns: Vec<Status>,
+    pub tags: Vec<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+impl Filter {
+    pub fn matches(&self, task: &Task) -> bool {
+        let day = task
+            .last_update()
+            .map(|t| t.with_timezone(&Local).date_naive());
+        (self.columns.is_empty() || self.columns.contains(&task.status))
+            && (self.tags.is_empty() || self.tags.iter().any(|t| task.tags.contains(t)))
+            && self
+                .since
+                .is_none_or(|since| day.is_some_and(|d| d >= since))
+            && self
+                .until
+                .is_none_or(|until| day.is_some_and(|d| d <= until))
+    }
+}
+
+pub fn export(tasks: &[Task], format: Format, filter: &Filter) -> Result<String> {
+    let tasks: Vec<&Task> = tasks.iter().filter(|t| filter.matches(t)).collect();
+    Ok(match format {
+        Format::Markdown => markdown(&tasks),
+        Format::Csv => csv(&tasks),
+        Format::Html => html(&tasks),
+        Format::Json => serde_json::to_string_pretty(&tasks)? + "\n",
+    })
+}
+
+/// GitHub-flavored checklist grouped by column.
+fn markdown(tasks: &[&Task]) -> String {
+    let mut out = String::new();
+    for (status, name) in COLUMNS {
+        let column: Vec<_> = tasks.iter().filter(|t| t.status == status).collect();
+        if column.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {} ({})\n\n", name, column.len()));
+        for task in column {
+            let mark = if status == Status::Done { "x" } else { " " };
+            out.push_str(&format!("- [{}] {}", mark, task.title));
+            if let Some(p) = task.priority {
+                out.push_str(&format!(" **P{}**", p));
+            }
+            if let Some(name) = &task.assignee {
+                out.push_str(&format!(" @{}", name));
+            }
+            if let Some(points) = task.estimate {
+                out.push_str(&format!(" [{}pt]", points));
+            }
+            if let Some(due) = task.due {
+                out.push_str(&format!(" (due {})", due));
+            }
+            if let Some(rule) = &task.repeat {
+                out.push_str(&format!(" (repeats {})", rule));
+            }
+            for tag in &task.tags {
+                out.push_str(&format!(" `#{}`", tag));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn csv(tasks: &[&Task]) -> String {
+    let mut out = csv::write_record(&[
+        "title",
+        "status",
+        "tags",
+        "priority",
+        "due",
+        "assignee",
+        "estimate",
+        "repeat",
+        "created",
+        "updated",
+        "description",
+    ]);
+    out.push('\n');
+    let date = |d: Option<DateTime<Utc>>| d.map(|d| d.to_rfc3339()).unwrap_or_default();
+    for task in tasks {
+        out.push_str(&csv::write_record(&[
+            task.title.clone(),
+            task.status.name().to_string(),
+            task.tags.join(";"),
+            task.priority.map(|p| format!("p{}", p)).unwrap_or_default(),
+            task.due.map(|d| d.to_string()).unwrap_or_default(),
+            task.assignee.clone().unwrap_or_default(),
+            task.estimate.map(|e| e.to_string()).unwrap_or_default(),
+            task.repeat
+                .as_ref()
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+            date(task.created_at),
+            date(task.updated_at),
+            task.description.clone(),
+        ]));
+        out.push('\n');
+    }
+    out
+}
+
+/// A static page with inline CSS, no external assets.
+fn html(tasks: &[&Task]) -> String {
+    let mut columns = String::new();
+    for (status, name) in COLUMNS {
+        let cards: Vec<_> = tasks.iter().filter(|t| t.status == status).collect();
+        columns.push_str(&format!(
+            "<section class=\"column\"><h2>{} <span class=\"count\">{}</span></h2>\n",
+            name,
+            cards.len()
+        ));
+        for task in cards {
+            columns.push_str("<article class=\"card\">");
+            columns.push_str(&format!("<h3>{}</h3>", escape(&task.title)));
+            let mut meta = Vec::new();
+            if let Some(p) = task.priority {
+                meta.push(format!("P{}", p));
+            }
+            if let Some(name) = &task.assignee {
+                meta.push(format!("@{}", escape(name)));
+            }
+            if let Some(points) = task.estimate {
+                meta.push(format!("{}pt", points));
+            }
+            if let Some(due) = task.due {
+                meta.push(format!("due {}", due));
+            }
+            if let Some(rule) = &task.repeat {
+                meta.push(format!("↻ {}", rule));
+            }
+            if !meta.is_empty() {
+                columns.push_str(&format!("<p class=\"meta\">{}</p>", meta.join(" · ")));
+            }
+            if !task.tags.is_empty() {
+                columns.push_str("<p class=\"tags\">");
+                for tag in &task.tags {
+                    columns.push_str(&format!("<span class=\"tag\">#{}</span> ", escape(tag)));
+                }
+                columns.push_str("</p>");
+            }
+            if !task.description.is_empty() {
+                columns.push_str(&format!("<pre>{}</pre>", escape(&task.description)));
+            }
+            columns.push_str("</article>\n");
+        }
+        columns.push_str("</section>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Kanban Board</title>
+<style>
+body {{ font-family: system-ui, sans-serif; background: #f4f5f7; margin: 0; padding: 1.5rem; }}
+h1 {{ margin-top: 0; }}
+.board {{ display: grid; grid-template-columns: repeat(3, 1fr); gap: 1rem; align-items: start; }}
+.column {{ background: #ebecf0; border-radius: 8px; padding: 0.75rem; }}
+.column h2 {{ font-size: 1rem; margin: 0 0 0.75rem; }}
+.count {{ color: #6b778c; font-weight: normal; }}
+.card {{ background: #fff; border-radius: 6px; padding: 0.6rem 0.75rem; margin-bottom: 0.6rem; box-shadow: 0 1px 2px rgba(0,0,0,.15); }}
+.card h3 {{ font-size: 0.95rem; margin: 0; }}
+.tags {{ margin: 0.4rem 0 0; }}
+.meta {{ margin: 0.3rem 0 0; font-size: 0.8rem; color: #de350b; }}
+.tag {{ background: #dfe1e6; border-radius: 3px; padding: 0 0.3rem; font-size: 0.8rem; }}
+pre {{ white-space: pre-wrap; font-size: 0.8rem; color: #42526e; margin: 0.5rem 0 0; }}
+</style>
+</head>
+<body>
+<h1>Kanban Board</h1>
+<p class="count">Exported {}</p>
+<div class="board">
+{}</div>
+</body>
+</html>
+"#,
+        Local::now().format("%Y-%m-%d %H:%M"),
+        columns
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,274 @@
+//! Editing a task in `$VISUAL` / `$EDITOR` as a Markdown file:
+//!
+//! ```text
+//! ---
+//! status: doing
+//! tags: bug, ui
+//! priority: p2
+//! due: 2026-01-31
+//! assignee: alice
+//! estimate: 3
+//! repeat: mon,thu
+//! sprint: s12
+//! parent: 12
+//! ---
+//! # Title
+//!
+//! Description
+//! ```
+
+use crate::app::{Task, parse_priority, parse_tags};
+use crate::quick_add::parse_task_id;
+use anyhow::{Context, Result, anyhow, bail};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Serializes a task to the Markdown-with-front-matter format.
+pub fn to_markdown(task: &Task) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("status: {}\n", task.status.name()));
+    out.push_str(&format!("tags: {}\n", task.tags.join(", ")));
+    let priority = task.priority.map(|p| format!("p{}", p)).unwrap_or_default();
+    out.push_str(&format!("priority: {}\n", priority));
+    let due = task.due.map(|d| d.to_string()).unwrap_or_default();
+    out.push_str(&format!("due: {}\n", due));
+    let assignee = task.assignee.as_deref().unwrap_or_default();
+    out.push_str(&format!("assignee: {}\n", assignee));
+    let estimate = task.estimate.map(|e| e.to_string()).unwrap_or_default();
+    out.push_str(&format!("estimate: {}\n", estimate));
+    let repeat = task
+        .repeat
+        .as_ref()
+        .map(|r| r.to_string())
+        .unwrap_or_default();
+    out.push_str(&format!("repeat: {}\n", repeat));
+    let sprint = task.sprint.as_deref().unwrap_or_default();
+    out.push_str(&format!("sprint: {}\n", sprint));
+    let parent = task.parent.map(|p| p.to_string()).unwrap_or_default();
+    out.push_str(&format!("parent: {}\n", parent));
+    out.push_str("---\n");
+    out.push_str(&format!("# {}\n", task.title));
+    if !task.description.is_empty() {
+        out.push('\n');
+        out.push_str(&task.description);
+        out.push('\n');
+    }
+    out
+}
+
+/// Parses the editor output back into `task`. The front matter is optional,
+/// the `# Title` line is not.
+pub fn apply_markdown(task: &mut Task, text: &str) -> Result<()> {
+    let mut lines = text.lines().peekable();
+    let mut status = task.status.clone();
+    let mut tags = task.tags.clone();
+    let mut priority = task.priority;
+    let mut due = task.due;
+    let mut assignee = task.assignee.clone();
+    let mut estimate = task.estimate;
+    let mut repeat = task.repeat.clone();
+    let mut sprint = task.sprint.clone();
+    let mut parent = task.parent;
+
+    if lines.peek().map(|l| l.trim()) == Some("---") {
+        lines.next();
+        let mut closed = false;
+        for (n, line) in lines.by_ref().enumerate() {
+            let line = line.trim();
+            if line == "---" {
+                closed = true;
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Front matter line {}: expected 'key: value'", n + 2))?;
+            match key.trim() {
+                "status" => status = value.trim().parse()?,
+                "tags" => tags = parse_tags(value),
+                "priority" => {
+                    let value = value.trim();
+                    priority = if value.is_empty() {
+                        None
+                    } else {
+                        Some(parse_priority(value)?)
+                    };
+                }
+                "due" => {
+                    let value = value.trim();
+                    due = if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.parse().map_err(|_| {
+                            anyhow!("Invalid due date '{}' (expected YYYY-MM-DD)", value)
+                        })?)
+                    };
+                }
+                "assignee" => {
+                    let value = value.trim().trim_start_matches('@');
+                    assignee = (!value.is_empty()).then(|| value.to_string());
+                }
+                "estimate" => {
+                    let value = value.trim();
+                    estimate = if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.parse().map_err(|_| {
+                            anyhow!("Invalid estimate '{}' (expected whole points)", value)
+                        })?)
+                    };
+                }
+                "repeat" => {
+                    let value = value.trim();
+                    repeat = if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.parse()?)
+                    };
+                }
+                "sprint" => {
+                    let value = value.trim();
+                    sprint = (!value.is_empty()).then(|| value.to_string());
+                }
+                "parent" => {
+                    let value = value.trim();
+                    parent = if value.is_empty() {
+                        None
+                    } else {
+                        Some(parse_task_id(value).map_err(|_| {
+                            anyhow!("Invalid parent '{}' (expected a task ID)", value)
+                        })?)
+                    };
+                }
+                other => bail!("Front matter line {}: unknown field '{}'", n + 2, other),
+            }
+        }
+        if !closed {
+            bail!("Front matter is not closed with '---'");
+        }
+    }
+
+    let title = loop {
+        match lines.next() {
+            Some(line) if line.trim().is_empty() => continue,
+            Some(line) => match line.strip_prefix("# ") {
+                Some(title) if !title.trim().is_empty() => break title.trim().to_string(),
+                _ => bail!("Expected a '# Title' line, found '{}'", line),
+            },
+            None => bail!("Missing '# Title' line"),
+        }
+    };
+    let description = lines.collect::<Vec<_>>().join("\n");
+
+    task.title = title;
+    task.description = description.trim_matches('\n').trim_end().to_string();
+    if status != task.status {
+        task.set_status(status);
+    }
+    task.tags = tags;
+    task.priority = priority;
+    task.due = due;
+    task.assignee = assignee;
+    task.estimate = estimate;
+    task.repeat = repeat;
+    task.sprint = sprint;
+    task.parent = parent;
+    Ok(())
+}
+
+/// Writes `content` to a temp file, opens it in the user's editor and
+/// returns the saved text. Blocks until the editor exits.
+pub fn run(content: &str) -> Result<String> {
+    let editor = editor();
+    let path = temp_file(content).context("Cannot create a temp file to edit")?;
+
+    // $EDITOR may carry arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Cannot start editor '{}'", editor));
+
+    let result = status.and_then(|status| {
+        if !status.success() {
+            bail!("Editor '{}' exited with {}", editor, status);
+        }
+        fs::read_to_string(&path).context("Cannot read the edited file")
+    });
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// Creates a new file with an unguessable name in the temp directory, so
+/// another user cannot plant a symlink there first. Only we can read it.
+fn temp_file(content: &str) -> io::Result<PathBuf> {
+    use std::hash::{BuildHasher, RandomState};
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut attempts = 0;
+    loop {
+        // Unpredictable from outside the process; a collision is retried
+        let name = format!(
+            "git-kanban-{}-{:016x}.md",
+            std::process::id(),
+            RandomState::new().hash_one(attempts)
+        );
+        let path = std::env::temp_dir().join(name);
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < 10 => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Opens `path` in the user's editor with the cursor on `line`.
+pub fn open_at(path: &Path, line: usize) -> Result<()> {
+    let editor = editor();
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let mut cmd = Command::new(program);
+    cmd.args(parts);
+
+    // VS Code and friends take `--goto file:line`, everything else `+line file`
+    let name = Path::new(program)
+        .file_name()
+        .map_or(program.into(), |n| n.to_string_lossy());
+    if ["code", "codium", "code-insiders", "cursor"].contains(&name.as_ref()) {
+        cmd.arg("--goto")
+            .arg(format!("{}:{}", path.display(), line));
+    } else {
+        cmd.arg(format!("+{}", line)).arg(path);
+    }
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("Cannot start editor '{}'", editor))?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+/// `$VISUAL`, then `$EDITOR`, then `vi`, like git. Empty ones are skipped.
+fn editor() -> String {
+    let var = |name| std::env::var(name).ok().filter(|e| !e.trim().is_empty());
+    var("VISUAL")
+        .or_else(|| var("EDITOR"))
+        .unwrap_or_else(|| "vi".to_string())
+}
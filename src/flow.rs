@@ -0,0 +1,92 @@
+//! Flow metrics for the analytics screen, built from the status changes
+//! recorded on every task (`Task::transitions`).
+
+use crate::app::{Status, Task, end_of_day};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+/// Days the cumulative flow diagram can cover, switched with Tab.
+pub const RANGES: [i64; 3] = [14, 30, 90];
+
+/// Percentiles of the cycle time drawn on the aging chart.
+pub const PERCENTILES: [u8; 3] = [50, 85, 95];
+
+/// Tasks per column at the end of each of the last `days` days, today last.
+pub fn cumulative_flow(
+    tasks: &[Task],
+    days: i64,
+    today: NaiveDate,
+) -> Vec<(NaiveDate, [usize; 3])> {
+    (0..days)
+        .rev()
+        .map(|ago| {
+            let day = today - Duration::days(ago);
+            let at = if ago == 0 {
+                Utc::now()
+            } else {
+                end_of_day(day)
+            };
+            let mut counts = [0; 3];
+            for status in tasks.iter().filter_map(|t| t.status_at(at)) {
+                counts[status.column()] += 1;
+            }
+            (day, counts)
+        })
+        .collect()
+}
+
+/// When work on the task started: its first move to DOING.
+pub fn started(task: &Task) -> Option<DateTime<Utc>> {
+    task.transitions
+        .iter()
+        .find(|t| t.status == Status::Doing)
+        .map(|t| t.at)
+}
+
+/// Days from the first move to DOING to the last move to DONE, for
+/// finished tasks that went through DOING.
+pub fn cycle_times(tasks: &[Task]) -> Vec<f64> {
+    let mut times: Vec<f64> = tasks
+        .iter()
+        .filter(|t| t.status == Status::Done)
+        .filter_map(|t| {
+            let start = started(t)?;
+            let done = t
+                .transitions
+                .iter()
+                .rev()
+                .find(|t| t.status == Status::Done)?;
+            Some(days(done.at - start))
+        })
+        .collect();
+    times.sort_by(f64::total_cmp);
+    times
+}
+
+/// Nearest-rank percentile of sorted `values`.
+pub fn percentile<T: Copy>(values: &[T], p: u8) -> Option<T> {
+    if values.is_empty() {
+        return None;
+    }
+    let rank = (p as f64 / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
+
+/// Tasks in DOING and how many days ago they were started, oldest first.
+/// Tasks moved there before transitions were recorded count from their
+/// last update.
+pub fn aging_wip(tasks: &[Task], now: DateTime<Utc>) -> Vec<(&Task, f64)> {
+    let mut wip: Vec<(&Task, f64)> = tasks
+        .iter()
+        .filter(|t| t.status == Status::Doing)
+        .filter_map(|t| {
+            let start = started(t).or(t.last_update())?;
+            Some((t, days(now - start)))
+        })
+        .collect();
+    wip.sort_by(|a, b| b.1.total_cmp(&a.1));
+    wip
+}
+
+fn days(d: Duration) -> f64 {
+    d.num_seconds().max(0) as f64 / 86_400.0
+}
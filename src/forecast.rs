@@ -0,0 +1,253 @@
+//! Monte Carlo delivery forecasts (`git kanban forecast` and the forecast
+//! panel). Each trial replays days drawn at random from the recent daily
+//! throughput, the number of tasks that reached DONE on each day.
+
+use crate::app::{Board, Status, Task};
+use crate::{flow, sprint};
+use chrono::{Duration, Local, NaiveDate};
+
+/// Days of throughput the trials draw from.
+pub const HISTORY_DAYS: i64 = 90;
+
+/// Confidence levels the forecast answers at.
+pub const CONFIDENCE: [u8; 3] = [50, 85, 95];
+
+const TRIALS: usize = 10_000;
+
+/// Longest forecast and history. A trial that has not finished the tasks
+/// after this many days gives up.
+pub const MAX_DAYS: usize = 3650;
+
+/// Fixed so the same history gives the same forecast.
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Days between the states kept of each trial, see `Trial`.
+const STRIDE: usize = 64;
+
+/// Tasks that reached DONE on each day, oldest first and today last.
+/// Starts at the first recorded status change if the board is younger than
+/// `days`, so the days before it existed do not count as idle.
+pub fn throughput(tasks: &[Task], days: i64, today: NaiveDate) -> Vec<u32> {
+    let first = tasks
+        .iter()
+        .filter_map(|t| t.transitions.first().map(|t| t.at).or(t.last_update()))
+        .map(|at| at.with_timezone(&Local).date_naive())
+        .min()
+        .unwrap_or(today);
+    let start = (today - Duration::days(days - 1)).max(first.min(today));
+    let mut counts = vec![0; (today - start).num_days() as usize + 1];
+    for task in tasks.iter().filter(|t| t.status == Status::Done) {
+        if let Some(day) = finished(task).filter(|d| *d >= start && *d <= today) {
+            counts[(day - start).num_days() as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// The last move to DONE, or the last update for tasks finished before
+/// status changes were recorded.
+fn finished(task: &Task) -> Option<NaiveDate> {
+    let done = task
+        .transitions
+        .iter()
+        .rev()
+        .find(|t| t.status == Status::Done);
+    done.map(|t| t.at)
+        .or(task.last_update())
+        .map(|at| at.with_timezone(&Local).date_naive())
+}
+
+/// What to forecast when nothing is asked: every unfinished task, and the
+/// end of the running sprint or else two weeks from today.
+pub fn defaults(board: &Board, tasks: &[Task], today: NaiveDate) -> (u32, NaiveDate) {
+    let items = tasks.iter().filter(|t| t.status != Status::Done).count() as u32;
+    let by = sprint::active(&board.sprints)
+        .map(|s| s.end)
+        .filter(|end| *end > today)
+        .unwrap_or(today + Duration::days(14));
+    (items, by)
+}
+
+/// Answers for `items` tasks and the date `by`, one per `CONFIDENCE` level.
+pub struct Forecast {
+    pub items: u32,
+    pub by: NaiveDate,
+    /// Tasks done at least, by `by`.
+    pub how_many: Vec<(u8, u32)>,
+    /// Date the tasks are done by, `None` if the history has no throughput.
+    pub when: Vec<(u8, Option<NaiveDate>)>,
+    /// The throughput the trials drew from.
+    pub history: Vec<u32>,
+    trials: Vec<Trial>,
+}
+
+impl Forecast {
+    /// Simulates the days after `today`, up to `MAX_DAYS` of them.
+    pub fn new(history: &[u32], today: NaiveDate, items: u32, by: NaiveDate) -> Forecast {
+        let mut forecast = Forecast {
+            items,
+            by,
+            how_many: Vec::new(),
+            when: Vec::new(),
+            history: history.to_vec(),
+            trials: (0..TRIALS as u64).map(Trial::new).collect(),
+        };
+        forecast.ask(today, items, by);
+        forecast
+    }
+
+    /// Answers for other `items` and `by` from the same trials, replaying
+    /// only the days the kept states do not cover yet.
+    pub fn ask(&mut self, today: NaiveDate, items: u32, by: NaiveDate) {
+        let days = ((by - today).num_days().max(0) as usize).min(MAX_DAYS);
+        let idle = self.history.iter().all(|&n| n == 0);
+        let history = &self.history;
+
+        let mut done: Vec<u32> = self
+            .trials
+            .iter_mut()
+            .map(|t| t.done_after(history, days))
+            .collect();
+        done.sort_unstable();
+
+        let mut needed: Vec<usize> = if idle {
+            Vec::new()
+        } else {
+            self.trials
+                .iter_mut()
+                .map(|t| t.days_for(history, items))
+                .collect()
+        };
+        needed.sort_unstable();
+
+        self.items = items;
+        self.by = by;
+        // 85% of the trials finished at least the 15th percentile
+        self.how_many = CONFIDENCE
+            .iter()
+            .map(|&c| (c, flow::percentile(&done, 100 - c).unwrap_or(0)))
+            .collect();
+        self.when = CONFIDENCE
+            .iter()
+            .map(|&c| {
+                let day = flow::percentile(&needed, c).filter(|&d| d < MAX_DAYS);
+                (c, day.map(|d| today + Duration::days(d as i64)))
+            })
+            .collect();
+    }
+
+    /// `12 tasks done in the last 30 days (0.4 a day)`
+    pub fn throughput(&self) -> String {
+        let done: u32 = self.history.iter().sum();
+        format!(
+            "{} tasks done in the last {} days ({:.1} a day)",
+            done,
+            self.history.len(),
+            done as f64 / self.history.len() as f64
+        )
+    }
+
+    /// The report printed by `git kanban forecast`.
+    pub fn to_text(&self, today: NaiveDate) -> String {
+        let mut out = format!("Throughput: {}\n\n", self.throughput());
+        out.push_str(&format!(
+            "How many by {} ({} days):\n",
+            self.by,
+            (self.by - today).num_days().max(0)
+        ));
+        for (c, n) in &self.how_many {
+            out.push_str(&format!("  {}%: {} or more\n", c, n));
+        }
+        out.push_str(&format!("\nWhen will {} tasks be done:\n", self.items));
+        for (c, date) in &self.when {
+            match date {
+                Some(date) => out.push_str(&format!("  {}%: by {}\n", c, date)),
+                None => out.push_str(&format!("  {}%: no throughput to forecast from\n", c)),
+            }
+        }
+        out
+    }
+}
+
+/// One run of days drawn from the history. Only the tasks done and the
+/// generator every `STRIDE` days are kept, so asking again replays at most
+/// `STRIDE` days instead of the whole run.
+struct Trial {
+    /// Before day `STRIDE * i`: tasks done so far and the generator.
+    states: Vec<(u32, Rng)>,
+}
+
+impl Trial {
+    fn new(n: u64) -> Trial {
+        Trial {
+            states: vec![(
+                0,
+                Rng::new(SEED ^ (n + 1).wrapping_mul(0xbf58_476d_1ce4_e5b9)),
+            )],
+        }
+    }
+
+    /// Keeps states up to the one before day `STRIDE * i`.
+    fn extend(&mut self, history: &[u32], i: usize) {
+        while self.states.len() <= i {
+            let (mut total, mut rng) = *self.states.last().expect("first state");
+            for _ in 0..STRIDE {
+                total += history[rng.below(history.len())];
+            }
+            self.states.push((total, rng));
+        }
+    }
+
+    /// Tasks done after `days` days.
+    fn done_after(&mut self, history: &[u32], days: usize) -> u32 {
+        self.extend(history, days / STRIDE);
+        let (mut total, mut rng) = self.states[days / STRIDE];
+        for _ in 0..days % STRIDE {
+            total += history[rng.below(history.len())];
+        }
+        total
+    }
+
+    /// Days until `items` tasks are done, `MAX_DAYS` if that is not within
+    /// reach.
+    fn days_for(&mut self, history: &[u32], items: u32) -> usize {
+        let last = MAX_DAYS / STRIDE;
+        let mut i = 0;
+        while i < last {
+            self.extend(history, i + 1);
+            if self.states[i + 1].0 >= items {
+                break;
+            }
+            i += 1;
+        }
+        let (mut total, mut rng) = self.states[i];
+        let mut day = i * STRIDE;
+        while total < items && day < MAX_DAYS {
+            total += history[rng.below(history.len())];
+            day += 1;
+        }
+        day
+    }
+}
+
+/// Xorshift64*, plenty for drawing days at random.
+#[derive(Clone, Copy)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (((self.next() >> 32) * n as u64) >> 32) as usize
+    }
+}
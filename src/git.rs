@@ -0,0 +1,55 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Runs `git <args>` in `dir` (or the current directory) and returns stdout.
+pub fn git(args: &[&str], dir: Option<&Path>) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = cmd.output().context("Cannot run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Top-level directory of the repository containing the current directory.
+pub fn repo_root() -> Result<PathBuf> {
+    let root = git(&["rev-parse", "--show-toplevel"], None).context("Not a git repository")?;
+    Ok(PathBuf::from(root.trim()))
+}
+
+/// Commit hash of `HEAD`, or `None` before the first commit.
+pub fn head(root: &Path) -> Option<String> {
+    git(&["rev-parse", "HEAD"], Some(root))
+        .ok()
+        .map(|h| h.trim().to_string())
+}
+
+/// Tracked files that differ from `HEAD`, staged or not.
+pub fn changed_files(root: &Path) -> Vec<String> {
+    git(&["diff", "--name-only", "-z", "HEAD"], Some(root))
+        .map(|out| {
+            out.split('\0')
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `HEAD` if `path` has no uncommitted changes, so that its line numbers in
+/// the working tree are also its line numbers at that commit.
+pub fn head_if_clean(root: &Path, path: &str) -> Option<String> {
+    let changed = git(&["diff", "--name-only", "HEAD", "--", path], Some(root)).ok()?;
+    changed.trim().is_empty().then(|| head(root)).flatten()
+}
@@ -0,0 +1,762 @@
+//! Reads tasks from other tools' export files (`git kanban import`).
+
+use crate::app::{Status, Task, Transition, parse_priority, parse_tags};
+use crate::csv;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Trello,
+    Github,
+    Taskwarrior,
+    TodoTxt,
+    Csv,
+}
+
+impl FromStr for Source {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "trello" => Ok(Source::Trello),
+            "github" => Ok(Source::Github),
+            "taskwarrior" | "task" => Ok(Source::Taskwarrior),
+            "todotxt" | "todo.txt" => Ok(Source::TodoTxt),
+            "csv" => Ok(Source::Csv),
+            _ => bail!(
+                "Unknown source '{}' (expected trello, github, taskwarrior, todotxt or csv)",
+                s
+            ),
+        }
+    }
+}
+
+/// Parses the contents of an export file into new tasks.
+pub fn read(source: Source, text: &str) -> Result<Vec<Task>> {
+    match source {
+        Source::Trello => trello(text).context("Cannot read the Trello board export"),
+        Source::Github => github(text).context("Cannot read the GitHub issues export"),
+        Source::Taskwarrior => taskwarrior(text).context("Cannot read the Taskwarrior export"),
+        Source::TodoTxt => Ok(text.lines().filter_map(todo_txt).collect()),
+        Source::Csv => csv_tasks(text),
+    }
+}
+
+/// Imported tasks split into those to add and those already on the board
+/// (or earlier in the same file), matched by title.
+pub struct Plan {
+    pub new: Vec<Task>,
+    pub duplicates: Vec<Task>,
+}
+
+pub fn plan(existing: &[Task], imported: Vec<Task>) -> Plan {
+    let mut seen: Vec<String> = existing.iter().map(|t| dedup_key(&t.title)).collect();
+    let mut plan = Plan {
+        new: Vec::new(),
+        duplicates: Vec::new(),
+    };
+    for task in imported {
+        let key = dedup_key(&task.title);
+        if seen.contains(&key) {
+            plan.duplicates.push(task);
+        } else {
+            seen.push(key);
+            plan.new.push(task);
+        }
+    }
+    plan
+}
+
+/// Case- and whitespace-insensitive title.
+fn dedup_key(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Guesses the column from a list or state name such as "In Progress".
+fn column_for(name: &str) -> Status {
+    let name = name.to_lowercase();
+    if ["done", "complete", "finished", "closed", "shipped"]
+        .iter()
+        .any(|w| name.contains(w))
+    {
+        Status::Done
+    } else if ["doing", "progress", "review", "wip", "started", "active"]
+        .iter()
+        .any(|w| name.contains(w))
+    {
+        Status::Doing
+    } else {
+        Status::Todo
+    }
+}
+
+/// Labels become tags: lowercase, spaces replaced by dashes.
+fn label_tag(label: &str) -> String {
+    label
+        .trim()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// `P1`, `priority: high`, `priority/2` -> the priority, other labels -> None.
+fn label_priority(label: &str) -> Option<u8> {
+    let lower = label.trim().to_lowercase();
+    let value = match lower.strip_prefix("priority") {
+        Some(rest) => rest.trim_start_matches([':', '/', '-', '_', ' ']),
+        None if lower.starts_with('p') => lower.as_str(),
+        None => return None,
+    };
+    parse_priority(value).ok()
+}
+
+/// Replaces the history `Task::new` started at import time: created in
+/// TODO at `created_at`, then in its status from `reached` (or the last
+/// update). Without a creation date the task existed by `reached` at least.
+fn backdate(task: &mut Task, reached: Option<DateTime<Utc>>) {
+    let reached = reached.or(task.updated_at).unwrap_or_else(Utc::now);
+    let created = task.created_at.map_or(reached, |c| c.min(reached));
+    task.created_at = Some(created);
+    task.transitions = vec![Transition {
+        at: created,
+        status: Status::Todo,
+    }];
+    if task.status != Status::Todo {
+        task.transitions.push(Transition {
+            at: reached,
+            status: task.status.clone(),
+        });
+    }
+}
+
+fn date_of(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|d| d.date_naive())
+        .or_else(|_| NaiveDate::parse_from_str(timestamp, "%Y-%m-%d"))
+        .ok()
+}
+
+// Trello: Board menu > Print, export and share > Export as JSON
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloBoard {
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    cards: Vec<TrelloCard>,
+    #[serde(default)]
+    checklists: Vec<TrelloChecklist>,
+}
+
+#[derive(Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    due: Option<String>,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    #[serde(default)]
+    id_checklists: Vec<String>,
+    date_last_activity: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloChecklist {
+    id: String,
+    name: String,
+    #[serde(default)]
+    check_items: Vec<TrelloCheckItem>,
+}
+
+#[derive(Deserialize)]
+struct TrelloCheckItem {
+    name: String,
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+/// Open cards on open lists; checklists are appended to the description
+/// as Markdown checkboxes.
+fn trello(text: &str) -> Result<Vec<Task>> {
+    let board: TrelloBoard = serde_json::from_str(text)?;
+    let mut tasks = Vec::new();
+    for card in board.cards.iter().filter(|c| !c.closed) {
+        let list = board.lists.iter().find(|l| l.id == card.id_list);
+        if list.is_some_and(|l| l.closed) {
+            continue;
+        }
+        let status = list.map_or(Status::Todo, |l| column_for(&l.name));
+        let mut task = Task::new(card.name.trim(), status);
+        task.description = card.desc.trim().to_string();
+        task.due = card.due.as_deref().and_then(date_of);
+        for label in &card.labels {
+            let name = if label.name.is_empty() {
+                label.color.clone().unwrap_or_default()
+            } else {
+                label.name.clone()
+            };
+            match label_priority(&name) {
+                Some(p) => task.priority = Some(p),
+                None if !name.is_empty() => task.tags.push(label_tag(&name)),
+                None => {}
+            }
+        }
+        for id in &card.id_checklists {
+            let Some(checklist) = board.checklists.iter().find(|c| &c.id == id) else {
+                continue;
+            };
+            let mut items: Vec<_> = checklist.check_items.iter().collect();
+            items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+            if !task.description.is_empty() {
+                task.description.push_str("\n\n");
+            }
+            task.description
+                .push_str(&format!("**{}**\n", checklist.name));
+            for item in items {
+                let mark = if item.state == "complete" { "x" } else { " " };
+                task.description
+                    .push_str(&format!("\n- [{}] {}", mark, item.name));
+            }
+        }
+        if let Some(activity) = card.date_last_activity {
+            task.updated_at = Some(activity);
+        }
+        backdate(&mut task, card.date_last_activity);
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+// GitHub: `gh issue list --state all --json number,title,body,state,labels,url,milestone`
+// or the REST API's `/repos/{owner}/{repo}/issues` response.
+
+#[derive(Deserialize)]
+struct GithubIssue {
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    number: Option<u64>,
+    url: Option<String>,
+    html_url: Option<String>,
+    milestone: Option<GithubMilestone>,
+    pull_request: Option<serde_json::Value>,
+    #[serde(alias = "createdAt")]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(alias = "updatedAt")]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(alias = "closedAt")]
+    closed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct GithubLabel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GithubMilestone {
+    #[serde(alias = "dueOn")]
+    due_on: Option<String>,
+}
+
+/// Closed issues go to DONE, open ones labelled "in progress" (or similar)
+/// to DOING. Pull requests are skipped.
+fn github(text: &str) -> Result<Vec<Task>> {
+    let issues: Vec<GithubIssue> = serde_json::from_str(text)?;
+    let mut tasks = Vec::new();
+    for issue in issues.into_iter().filter(|i| i.pull_request.is_none()) {
+        let closed = issue.state.eq_ignore_ascii_case("closed");
+        let doing =
+            |name: &str| label_priority(name).is_none() && column_for(name) == Status::Doing;
+        // The status is decided first so the task's history starts there
+        let status = if closed {
+            Status::Done
+        } else if issue.labels.iter().any(|l| doing(&l.name)) {
+            Status::Doing
+        } else {
+            Status::Todo
+        };
+        let mut task = Task::new(issue.title.trim(), status);
+        for label in &issue.labels {
+            if let Some(p) = label_priority(&label.name) {
+                task.priority = Some(p);
+            } else if closed || !doing(&label.name) {
+                task.tags.push(label_tag(&label.name));
+            }
+        }
+
+        task.description = issue.body.unwrap_or_default().trim().to_string();
+        let link = issue.html_url.or(issue.url);
+        if let Some(link) = link.filter(|l| !l.contains("api.github.com")) {
+            if !task.description.is_empty() {
+                task.description.push_str("\n\n");
+            }
+            match issue.number {
+                Some(n) => task.description.push_str(&format!("[#{}]({})", n, link)),
+                None => task.description.push_str(&format!("<{}>", link)),
+            }
+        }
+        task.due = issue
+            .milestone
+            .and_then(|m| m.due_on)
+            .as_deref()
+            .and_then(date_of);
+        task.created_at = issue.created_at.or(task.created_at);
+        task.updated_at = issue.updated_at.or(task.updated_at);
+        backdate(&mut task, issue.closed_at.filter(|_| closed));
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+// Taskwarrior: `task export`
+
+#[derive(Deserialize)]
+struct TaskwarriorTask {
+    description: String,
+    status: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    project: Option<String>,
+    priority: Option<String>,
+    due: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    entry: Option<String>,
+    modified: Option<String>,
+    #[serde(default)]
+    annotations: Vec<TaskwarriorAnnotation>,
+}
+
+#[derive(Deserialize)]
+struct TaskwarriorAnnotation {
+    description: String,
+}
+
+/// Pending tasks go to TODO, started ones to DOING and completed ones to
+/// DONE. Deleted tasks and recurrence templates are skipped.
+fn taskwarrior(text: &str) -> Result<Vec<Task>> {
+    // Versions before 2.6 print one object per line instead of an array
+    let items: Vec<TaskwarriorTask> = if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)?
+    } else {
+        text.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l.trim_end_matches(',')))
+            .collect::<serde_json::Result<_>>()?
+    };
+
+    let mut tasks = Vec::new();
+    for item in items {
+        let status = match item.status.as_str() {
+            "completed" => Status::Done,
+            "pending" | "waiting" if item.start.is_some() => Status::Doing,
+            "pending" | "waiting" => Status::Todo,
+            _ => continue,
+        };
+        let mut task = Task::new(item.description.trim(), status);
+        task.tags = item.tags.iter().map(|t| label_tag(t)).collect();
+        if let Some(project) = item.project {
+            task.tags.push(label_tag(&project));
+        }
+        task.priority = item
+            .priority
+            .as_deref()
+            .and_then(|p| parse_priority(p).ok());
+        task.due = item
+            .due
+            .as_deref()
+            .and_then(taskwarrior_date)
+            .map(|d| d.date_naive());
+        task.description = item
+            .annotations
+            .iter()
+            .map(|a| format!("- {}", a.description))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(entry) = item.entry.as_deref().and_then(taskwarrior_date) {
+            task.created_at = Some(entry);
+        }
+        if let Some(modified) = item.modified.as_deref().and_then(taskwarrior_date) {
+            task.updated_at = Some(modified);
+        }
+        let reached = match task.status {
+            Status::Done => item.end.as_deref(),
+            _ => item.start.as_deref(),
+        };
+        backdate(&mut task, reached.and_then(taskwarrior_date));
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+/// `20260131T120000Z`
+fn taskwarrior_date(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|d| d.and_utc())
+}
+
+/// `x 2026-01-31 2026-01-02 (A) Call Mom +family @phone due:2026-02-01`
+///
+/// `+project` and `@context` become tags, `due:` and `pri:` fields are
+/// read, other `key:value` pairs stay in the title.
+fn todo_txt(line: &str) -> Option<Task> {
+    let mut rest = line.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let done = rest.starts_with("x ");
+    if done {
+        rest = rest[2..].trim_start();
+    }
+    let mut priority = todo_txt_priority_prefix(&mut rest);
+
+    // Completion date (done tasks only), then creation date
+    let mut dates = Vec::new();
+    while dates.len() < if done { 2 } else { 1 } {
+        let Some((word, tail)) = rest.split_once(' ') else {
+            break;
+        };
+        let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") else {
+            break;
+        };
+        dates.push(date);
+        rest = tail.trim_start();
+    }
+    let created = if done { dates.get(1) } else { dates.first() };
+    // Some tools keep the priority of a done task after its dates
+    priority = priority.or_else(|| todo_txt_priority_prefix(&mut rest));
+
+    let mut title = Vec::new();
+    let mut tags = Vec::new();
+    let mut due = None;
+    for word in rest.split_whitespace() {
+        if let Some(tag) = word
+            .strip_prefix('+')
+            .or_else(|| word.strip_prefix('@'))
+            .filter(|t| !t.is_empty())
+        {
+            tags.extend(parse_tags(tag));
+        } else if let Some(date) = word.strip_prefix("due:") {
+            due = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        } else if let Some(letter) = word.strip_prefix("pri:") {
+            priority = letter
+                .bytes()
+                .next()
+                .filter(u8::is_ascii_alphabetic)
+                .map(|b| todo_txt_priority(b.to_ascii_uppercase()));
+        } else {
+            title.push(word);
+        }
+    }
+    if title.is_empty() {
+        return None;
+    }
+
+    let status = if done { Status::Done } else { Status::Todo };
+    let mut task = Task::new(title.join(" "), status);
+    task.tags = tags;
+    task.priority = priority;
+    task.due = due;
+    let midnight = |d: &NaiveDate| d.and_hms_opt(0, 0, 0).map(|d| d.and_utc());
+    if let Some(created) = created.and_then(midnight) {
+        task.created_at = Some(created);
+    }
+    let completed = dates.first().filter(|_| done).and_then(midnight);
+    if let Some(completed) = completed {
+        task.updated_at = Some(completed);
+    }
+    backdate(&mut task, completed);
+    Some(task)
+}
+
+/// Takes a leading `(A) ` off `rest`.
+fn todo_txt_priority_prefix(rest: &mut &str) -> Option<u8> {
+    let [b'(', letter, b')', b' ', ..] = rest.as_bytes() else {
+        return None;
+    };
+    let letter = *letter;
+    if !letter.is_ascii_uppercase() {
+        return None;
+    }
+    *rest = rest[4..].trim_start();
+    Some(todo_txt_priority(letter))
+}
+
+/// `A` -> 1 ... `I` and below -> 9
+fn todo_txt_priority(letter: u8) -> u8 {
+    (letter - b'A' + 1).min(9)
+}
+
+/// The first row names the columns. Recognized (case-insensitive):
+/// title/name/summary, description/body/notes, status/column/list/state,
+/// tags/labels, priority, due, created and updated, which also makes
+/// `git kanban export --format csv` round-trip.
+fn csv_tasks(text: &str) -> Result<Vec<Task>> {
+    let mut records = csv::parse(text).into_iter();
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+    };
+    let title = column(&["title", "name", "summary", "task"])
+        .context("CSV header has no title column (title, name, summary or task)")?;
+    let description = column(&["description", "body", "notes", "desc"]);
+    let status = column(&["status", "column", "list", "state"]);
+    let tags = column(&["tags", "labels", "tag", "label"]);
+    let priority = column(&["priority"]);
+    let due = column(&["due", "due date", "due_date"]);
+    let assignee = column(&["assignee", "owner", "assigned to"]);
+    let estimate = column(&["estimate", "points", "story points"]);
+    let repeat = column(&["repeat", "recurrence", "recurring"]);
+    let created = column(&["created", "created_at"]);
+    let updated = column(&["updated", "updated_at"]);
+
+    let mut tasks = Vec::new();
+    for (n, record) in records.enumerate() {
+        let field = |i: Option<usize>| {
+            i.and_then(|i| record.get(i))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+        let Some(name) = field(Some(title)) else {
+            continue;
+        };
+        let status = field(status).map_or(Status::Todo, |s| s.parse().unwrap_or(column_for(s)));
+        // Titles are single-line on the board
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut task = Task::new(name, status);
+        task.description = field(description).unwrap_or_default().to_string();
+        task.tags = field(tags)
+            .map(|t| parse_tags(&t.replace(';', ",")))
+            .unwrap_or_default();
+        task.priority = field(priority)
+            .map(parse_priority)
+            .transpose()
+            .with_context(|| format!("CSV row {}", n + 2))?;
+        task.due = field(due).and_then(date_of);
+        task.assignee = field(assignee).map(|a| a.trim_start_matches('@').to_string());
+        task.estimate = field(estimate).and_then(|e| e.parse().ok());
+        task.repeat = field(repeat)
+            .map(str::parse)
+            .transpose()
+            .with_context(|| format!("CSV row {}", n + 2))?;
+        if let Some(created) = field(created).and_then(|d| d.parse().ok()) {
+            task.created_at = Some(created);
+        }
+        if let Some(updated) = field(updated).and_then(|d| d.parse().ok()) {
+            task.updated_at = Some(updated);
+        }
+        backdate(&mut task, None);
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{self, Filter, Format};
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn closed_github_issue_keeps_its_history() {
+        let text = r#"[{"title": "Crash", "state": "CLOSED", "labels": [],
+            "createdAt": "2026-01-01T10:00:00Z", "updatedAt": "2026-01-05T10:00:00Z",
+            "closedAt": "2026-01-03T10:00:00Z"}]"#;
+        let task = &read(Source::Github, text).unwrap()[0];
+        let created = task.created_at.unwrap();
+        let closed: DateTime<Utc> = "2026-01-03T10:00:00Z".parse().unwrap();
+
+        assert_eq!(task.status_at(created - Duration::seconds(1)), None);
+        assert_eq!(
+            task.status_at(created + Duration::seconds(1)),
+            Some(Status::Todo)
+        );
+        assert_eq!(
+            task.status_at(closed - Duration::seconds(1)),
+            Some(Status::Todo)
+        );
+        assert_eq!(task.status_at(closed), Some(Status::Done));
+    }
+
+    #[test]
+    fn github_issues() {
+        let text = r#"[
+            {"title": "Open", "state": "OPEN", "number": 3, "body": " Body ",
+             "url": "https://github.com/o/r/issues/3",
+             "labels": [{"name": "In Progress"}, {"name": "P2"}, {"name": "Good First"}],
+             "milestone": {"dueOn": "2026-03-01T00:00:00Z"}},
+            {"title": "A PR", "state": "open", "pull_request": {}}
+        ]"#;
+        let tasks = read(Source::Github, text).unwrap();
+        assert_eq!(tasks.len(), 1);
+        let task = &tasks[0];
+        assert_eq!(task.status, Status::Doing);
+        assert_eq!(task.priority, Some(2));
+        assert_eq!(task.tags, vec!["good-first"]);
+        assert_eq!(
+            task.description,
+            "Body\n\n[#3](https://github.com/o/r/issues/3)"
+        );
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert!(read(Source::Github, "{").is_err());
+    }
+
+    #[test]
+    fn trello_board() {
+        let text = r#"{
+            "lists": [{"id": "l1", "name": "Doing"}, {"id": "l2", "name": "Old", "closed": true}],
+            "cards": [
+                {"name": "Card", "idList": "l1", "desc": "Desc", "idChecklists": ["c1"],
+                 "labels": [{"name": "", "color": "red"}, {"name": "priority: 1"}],
+                 "dateLastActivity": "2026-01-02T00:00:00Z"},
+                {"name": "Archived", "idList": "l1", "closed": true},
+                {"name": "On a closed list", "idList": "l2"}
+            ],
+            "checklists": [{"id": "c1", "name": "Steps", "checkItems": [
+                {"name": "second", "state": "incomplete", "pos": 2},
+                {"name": "first", "state": "complete", "pos": 1}
+            ]}]
+        }"#;
+        let tasks = read(Source::Trello, text).unwrap();
+        assert_eq!(tasks.len(), 1);
+        let task = &tasks[0];
+        assert_eq!(task.status, Status::Doing);
+        assert_eq!(task.tags, vec!["red"]);
+        assert_eq!(task.priority, Some(1));
+        assert_eq!(
+            task.description,
+            "Desc\n\n**Steps**\n\n- [x] first\n- [ ] second"
+        );
+        // Known to exist by its last activity
+        let active: DateTime<Utc> = "2026-01-02T00:00:00Z".parse().unwrap();
+        assert_eq!(task.status_at(active), Some(Status::Doing));
+    }
+
+    #[test]
+    fn taskwarrior_export() {
+        // One object per line, as before Taskwarrior 2.6
+        let text = concat!(
+            r#"{"description": "Started", "status": "pending", "start": "20260105T090000Z", "entry": "20260101T090000Z", "tags": ["Home Work"], "project": "house", "priority": "H"},"#,
+            "\n",
+            r#"{"description": "Gone", "status": "deleted"}"#,
+            "\n",
+            r#"{"description": "Finished", "status": "completed", "entry": "20260101T090000Z", "end": "20260110T090000Z"}"#,
+        );
+        let tasks = read(Source::Taskwarrior, text).unwrap();
+        assert_eq!(tasks.len(), 2);
+        let (started, finished) = (&tasks[0], &tasks[1]);
+        assert_eq!(started.status, Status::Doing);
+        assert_eq!(started.tags, vec!["home-work", "house"]);
+        assert_eq!(started.priority, Some(1));
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 1, d, 12, 0, 0).unwrap();
+        assert_eq!(started.status_at(day(2)), Some(Status::Todo));
+        assert_eq!(started.status_at(day(6)), Some(Status::Doing));
+        assert_eq!(finished.status_at(day(9)), Some(Status::Todo));
+        assert_eq!(finished.status_at(day(11)), Some(Status::Done));
+    }
+
+    #[test]
+    fn todo_txt_lines() {
+        let task =
+            todo_txt("x 2026-01-31 2026-01-02 (A) Call Mom +family @phone due:2026-02-01 k:v")
+                .unwrap();
+        assert_eq!(task.title, "Call Mom k:v");
+        assert_eq!(task.status, Status::Done);
+        assert_eq!(task.priority, Some(1));
+        assert_eq!(task.tags, vec!["family", "phone"]);
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2026, 2, 1));
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 1, d, 12, 0, 0).unwrap();
+        assert_eq!(task.status_at(day(2)), Some(Status::Todo));
+        assert_eq!(task.status_at(day(31)), Some(Status::Done));
+
+        let task = todo_txt("(Z) Süße Grüße pri:b").unwrap();
+        assert_eq!(task.title, "Süße Grüße");
+        assert_eq!(task.priority, Some(2));
+        assert_eq!(task.status, Status::Todo);
+        assert!(todo_txt("   ").is_none());
+        assert!(todo_txt("x +only-tags").is_none());
+    }
+
+    #[test]
+    fn csv_with_quoted_fields() {
+        let text = "Name,Notes,List,Labels,Priority\n\
+                    \"Fix, now\",\"line one\nline two\",In Progress,bug; ui,p2\n\
+                    ,skipped without a title,,,\n";
+        let tasks = read(Source::Csv, text).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Fix, now");
+        assert_eq!(tasks[0].description, "line one\nline two");
+        assert_eq!(tasks[0].status, Status::Doing);
+        assert_eq!(tasks[0].tags, vec!["bug", "ui"]);
+
+        let err = read(Source::Csv, "title,priority\nA,urgent\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("CSV row 2"), "{:#}", err);
+        assert!(read(Source::Csv, "summary\n").unwrap().is_empty());
+        assert!(read(Source::Csv, "foo,bar\n1,2\n").is_err());
+    }
+
+    #[test]
+    fn csv_round_trips_the_export() {
+        let mut task = Task::new("Ship \"v2\", finally", Status::Done);
+        task.description = "- [x] tag\n- [ ] announce".to_string();
+        task.tags = vec!["release".to_string()];
+        task.priority = Some(3);
+        let text = export::export(&[task.clone()], Format::Csv, &Filter::default()).unwrap();
+        let back = &read(Source::Csv, &text).unwrap()[0];
+        assert_eq!(back.title, task.title);
+        assert_eq!(back.description, task.description);
+        assert_eq!(back.status, task.status);
+        assert_eq!(back.tags, task.tags);
+        assert_eq!(back.priority, task.priority);
+        assert_eq!(back.created_at, task.created_at);
+    }
+
+    #[test]
+    fn plan_skips_known_titles() {
+        let existing = [Task::new("Write  Docs", Status::Todo)];
+        let imported = ["write docs", "New", "new"]
+            .map(|t| Task::new(t, Status::Todo))
+            .to_vec();
+        let plan = plan(&existing, imported);
+        assert_eq!(plan.new.len(), 1);
+        assert_eq!(plan.duplicates.len(), 2);
+    }
+}
@@ -1,6 +1,63 @@
-use crate::app::Task;
-use anyhow::Result;
-use std::{fs, path::PathBuf};
+//! Finding, reading and writing the task file.
+//!
+//! The file is `{ "version": N, "board": {...}, "tasks": [...] }`. Fields
+//! this version does not know about are kept in `extra` maps so that an
+//! older binary never drops data written by a newer one.
+
+use crate::app::{Board, Task};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Current file format version.
+pub const VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct File {
+    version: u32,
+    #[serde(default)]
+    board: Board,
+    #[serde(default)]
+    tasks: Vec<Task>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct FileRef<'a> {
+    version: u32,
+    board: &'a Board,
+    tasks: &'a [Task],
+    #[serde(flatten)]
+    extra: &'a Map<String, Value>,
+}
+
+/// The parts of the file outside the board and the tasks.
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    version: u32,
+    extra: Map<String, Value>,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Envelope {
+            version: VERSION,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Document {
+    pub envelope: Envelope,
+    pub board: Board,
+    pub tasks: Vec<Task>,
+}
 
 /// Determines where to save data.
 pub fn find_storage_path() -> Result<PathBuf> {
@@ -22,17 +79,84 @@ pub fn find_storage_path() -> Result<PathBuf> {
     Ok(current_dir.join(".kanban.json"))
 }
 
-pub fn load_tasks(file_path: &PathBuf) -> Vec<Task> {
-    if file_path.exists() {
-        let content = fs::read_to_string(file_path).unwrap_or_default();
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        Vec::new()
+/// Archived tasks: `.git/git-kanban.archive.json` or `.kanban.archive.json`,
+/// in the same format as the board.
+pub fn archive_path(data_path: &Path) -> PathBuf {
+    data_path.with_extension("archive.json")
+}
+
+/// Reads the task file, migrating older formats. A missing file is an
+/// empty board; an unreadable one is an error so it is never overwritten.
+pub fn load(file_path: &PathBuf) -> Result<Document> {
+    if !file_path.exists() {
+        return Ok(Document::default());
     }
+    let content = fs::read_to_string(file_path)
+        .with_context(|| format!("Cannot read {}", file_path.display()))?;
+    let value: Value = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not valid JSON", file_path.display()))?;
+    let file: File = serde_json::from_value(migrate(value)?)
+        .with_context(|| format!("Cannot read tasks from {}", file_path.display()))?;
+
+    let mut doc = Document {
+        envelope: Envelope {
+            version: file.version,
+            extra: file.extra,
+        },
+        board: file.board,
+        tasks: file.tasks,
+    };
+    doc.board.assign_ids(&mut doc.tasks);
+    Ok(doc)
 }
 
-pub fn save_tasks(file_path: &PathBuf, tasks: &[Task]) -> Result<()> {
-    let json = serde_json::to_string_pretty(tasks)?;
+pub fn save(file_path: &PathBuf, envelope: &Envelope, board: &Board, tasks: &[Task]) -> Result<()> {
+    let file = FileRef {
+        // Never downgrade a file written by a newer version
+        version: envelope.version.max(VERSION),
+        board,
+        tasks,
+        extra: &envelope.extra,
+    };
+    let json = serde_json::to_string_pretty(&file)?;
     fs::write(file_path, json)?;
     Ok(())
 }
+
+/// Upgrades `value` step by step to [`VERSION`]. Files from newer versions
+/// are read as they are; their unknown fields are kept on save.
+fn migrate(mut value: Value) -> Result<Value> {
+    let mut version = match &value {
+        Value::Array(_) => 0,
+        Value::Object(map) => map
+            .get("version")
+            .and_then(Value::as_u64)
+            .context("Task file has no 'version'")? as u32,
+        _ => bail!("Task file must be a JSON object or array"),
+    };
+    while version < VERSION {
+        value = MIGRATIONS[version as usize](value)?;
+        version += 1;
+    }
+    Ok(value)
+}
+
+/// `MIGRATIONS[n]` turns version `n` into version `n + 1`.
+const MIGRATIONS: [fn(Value) -> Result<Value>; VERSION as usize] = [v0_to_v1];
+
+/// The bare task array becomes the envelope; tasks get sequential IDs.
+fn v0_to_v1(value: Value) -> Result<Value> {
+    let Value::Array(mut tasks) = value else {
+        bail!("Expected a task array");
+    };
+    for (i, task) in tasks.iter_mut().enumerate() {
+        if let Value::Object(task) = task {
+            task.insert("id".to_string(), json!(i + 1));
+        }
+    }
+    Ok(json!({
+        "version": 1,
+        "board": { "next_id": tasks.len() + 1 },
+        "tasks": tasks,
+    }))
+}
@@ -0,0 +1,559 @@
+use crate::config::KeysConfig;
+use anyhow::{Result, anyhow, bail};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+/// Everything that can be bound to a key on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    New,
+    NewFromTemplate,
+    Edit,
+    View,
+    Delete,
+    MoveUp,
+    MoveDown,
+    Left,
+    Right,
+    Up,
+    Down,
+    Top,
+    Bottom,
+    Advance,
+    Scan,
+    Export,
+    Density,
+    Zoom,
+    Layout,
+    Filter,
+    Command,
+    Help,
+    Timer,
+    Sprint,
+    Analytics,
+    Forecast,
+    Tree,
+}
+
+impl Action {
+    pub const ALL: [Action; 28] = [
+        Action::Quit,
+        Action::New,
+        Action::NewFromTemplate,
+        Action::Edit,
+        Action::View,
+        Action::Delete,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::Top,
+        Action::Bottom,
+        Action::Advance,
+        Action::Scan,
+        Action::Export,
+        Action::Density,
+        Action::Zoom,
+        Action::Layout,
+        Action::Filter,
+        Action::Command,
+        Action::Help,
+        Action::Timer,
+        Action::Sprint,
+        Action::Analytics,
+        Action::Forecast,
+        Action::Tree,
+    ];
+
+    /// Name used in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::New => "new",
+            Action::NewFromTemplate => "new_from_template",
+            Action::Edit => "edit",
+            Action::View => "view",
+            Action::Delete => "delete",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Advance => "advance",
+            Action::Scan => "scan",
+            Action::Export => "export",
+            Action::Density => "density",
+            Action::Zoom => "zoom",
+            Action::Layout => "layout",
+            Action::Filter => "filter",
+            Action::Command => "command",
+            Action::Help => "help",
+            Action::Timer => "timer",
+            Action::Sprint => "sprint",
+            Action::Analytics => "analytics",
+            Action::Forecast => "forecast",
+            Action::Tree => "tree",
+        }
+    }
+
+    /// Shown in the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::New => "New task",
+            Action::NewFromTemplate => "New task from a template",
+            Action::Edit => "Edit task",
+            Action::View => "View task",
+            Action::Delete => "Delete task",
+            Action::MoveUp => "Move task up",
+            Action::MoveDown => "Move task down",
+            Action::Left => "Previous column",
+            Action::Right => "Next column",
+            Action::Up => "Previous task",
+            Action::Down => "Next task",
+            Action::Top => "First task",
+            Action::Bottom => "Last task",
+            Action::Advance => "Move task to the next column",
+            Action::Scan => "Import TODO comments",
+            Action::Export => "Export the board",
+            Action::Density => "Toggle cards",
+            Action::Zoom => "Zoom the column",
+            Action::Layout => "Cycle the layout",
+            Action::Filter => "Filter tasks",
+            Action::Command => "Command line",
+            Action::Help => "This help",
+            Action::Timer => "Start or stop the timer",
+            Action::Sprint => "Sprint progress and burndown",
+            Action::Analytics => "Cumulative flow and aging WIP",
+            Action::Forecast => "When the open tasks will be done",
+            Action::Tree => "Epics and their tasks as a tree",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+/// A single key press. Shift is folded into the character for `Char` keys,
+/// so `G` and `Shift+g` are the same key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            // Terminals report Shift+Tab as BackTab with Shift held
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            other => other,
+        };
+        Key { code, modifiers }
+    }
+
+    /// Parses `q`, `G`, `Enter`, `Shift+Up`, `Ctrl+s`, ...
+    pub fn parse(s: &str) -> Result<Self> {
+        if s == "+" {
+            return Ok(Key::new(KeyCode::Char('+'), KeyModifiers::NONE));
+        }
+        let parts: Vec<&str> = s.split('+').collect();
+        let (name, mods) = parts.split_last().ok_or_else(|| anyhow!("Empty key"))?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "shift" => KeyModifiers::SHIFT,
+                "alt" => KeyModifiers::ALT,
+                _ => bail!("Unknown modifier '{}' in '{}'", m, s),
+            };
+        }
+        let code = parse_code(name).ok_or_else(|| anyhow!("Unknown key '{}'", s))?;
+        Ok(Key::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Key::new(event.code, event.modifiers)
+    }
+}
+
+fn parse_code(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        f if f.starts_with('f') => KeyCode::F(f[1..].parse().ok()?),
+        _ => return None,
+    };
+    Some(code)
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// One or more keys pressed in order, e.g. `gg` or `dd`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySequence(Vec<Key>);
+
+impl KeySequence {
+    /// A binding is either a single key (`Shift+Up`, `Enter`, `q`),
+    /// space-separated keys (`g g`), or a run of plain characters (`gg`).
+    fn parse(s: &str) -> Result<Self> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let keys = match tokens.as_slice() {
+            [] => bail!("Empty key binding"),
+            [single] if !single.contains('+') && parse_code(single).is_none() => single
+                .chars()
+                .map(|c| Key::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .collect(),
+            tokens => tokens
+                .iter()
+                .map(|t| Key::parse(t))
+                .collect::<Result<_>>()?,
+        };
+        Ok(KeySequence(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in &self.0 {
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of feeding the pending keys to the keymap.
+pub enum Lookup {
+    Action(Action),
+    /// The keys so far start a longer binding; wait for more.
+    Pending,
+    None,
+}
+
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeySequence>)>,
+}
+
+impl Keymap {
+    pub fn from_config(config: &KeysConfig) -> Result<Self> {
+        let preset = config.preset.as_deref().unwrap_or("default");
+        let defaults = match preset {
+            "default" => DEFAULT_PRESET,
+            "vim" => VIM_PRESET,
+            other => bail!(
+                "Unknown key preset '{}' (expected 'default' or 'vim')",
+                other
+            ),
+        };
+
+        let mut bindings = Vec::new();
+        for action in Action::ALL {
+            let keys = match config.bindings.get(action.name()) {
+                Some(keys) => keys.iter().map(|k| k.as_str()).collect(),
+                None => preset_keys(defaults, action),
+            };
+            let sequences = keys
+                .into_iter()
+                .map(|k| {
+                    KeySequence::parse(k)
+                        .map_err(|e| anyhow!("Binding for '{}': {}", action.name(), e))
+                })
+                .collect::<Result<_>>()?;
+            bindings.push((action, sequences));
+        }
+
+        if let Some(name) = config
+            .bindings
+            .keys()
+            .find(|n| Action::from_name(n).is_none())
+        {
+            bail!("Unknown action '{}' in key bindings", name);
+        }
+        check_conflicts(&bindings)?;
+        Ok(Keymap { bindings })
+    }
+
+    /// Matches the keys typed so far. Bindings never start with another
+    /// binding (see `check_conflicts`), so at most one can match.
+    pub fn lookup(&self, pending: &[Key]) -> Lookup {
+        let mut is_prefix = false;
+        for (action, sequences) in &self.bindings {
+            for seq in sequences {
+                if seq.0 == pending {
+                    return Lookup::Action(*action);
+                }
+                if seq.0.starts_with(pending) {
+                    is_prefix = true;
+                }
+            }
+        }
+        if is_prefix {
+            Lookup::Pending
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// Every key sequence bound to `action`, as shown to the user.
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|(a, _)| *a == action)
+            .flat_map(|(_, seqs)| seqs.iter().map(|s| s.to_string()))
+            .collect()
+    }
+
+    /// Footer help text: the first key of each action, e.g. `q:Quit | n:New`.
+    pub fn footer_help(&self, entries: &[(&str, &[Action])]) -> String {
+        entries
+            .iter()
+            .filter_map(|(label, actions)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|a| {
+                        let (_, seqs) = self.bindings.iter().find(|(b, _)| b == a)?;
+                        seqs.first().map(|s| s.to_string())
+                    })
+                    .collect();
+                (!keys.is_empty()).then(|| format!("{}:{}", keys.join("/"), label))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+type Preset = &'static [(Action, &'static [&'static str])];
+
+/// Rejects a binding that starts with another one: `g` would fire before
+/// `gg` could be typed.
+fn check_conflicts(bindings: &[(Action, Vec<KeySequence>)]) -> Result<()> {
+    let all: Vec<_> = bindings
+        .iter()
+        .flat_map(|(action, sequences)| sequences.iter().map(move |seq| (*action, seq)))
+        .collect();
+    for (i, (action, seq)) in all.iter().enumerate() {
+        for (j, (other, longer)) in all.iter().enumerate() {
+            if i != j && longer.0.starts_with(&seq.0) && (longer.0.len() > seq.0.len() || i < j) {
+                bail!(
+                    "Key binding '{}' for '{}' conflicts with '{}' for '{}'",
+                    seq,
+                    action.name(),
+                    longer,
+                    other.name()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn preset_keys(preset: Preset, action: Action) -> Vec<&'static str> {
+    preset
+        .iter()
+        .find(|(a, _)| *a == action)
+        .map(|(_, keys)| keys.to_vec())
+        .unwrap_or_default()
+}
+
+const DEFAULT_PRESET: Preset = &[
+    (Action::Quit, &["q"]),
+    (Action::New, &["n"]),
+    (Action::NewFromTemplate, &["N"]),
+    (Action::Edit, &["e"]),
+    (Action::View, &["v"]),
+    (Action::Delete, &["d"]),
+    (Action::MoveUp, &["Shift+Up"]),
+    (Action::MoveDown, &["Shift+Down"]),
+    (Action::Left, &["Left"]),
+    (Action::Right, &["Right"]),
+    (Action::Up, &["Up"]),
+    (Action::Down, &["Down"]),
+    (Action::Top, &["Home"]),
+    (Action::Bottom, &["End"]),
+    (Action::Advance, &["Enter"]),
+    (Action::Scan, &["S"]),
+    (Action::Export, &["x"]),
+    (Action::Density, &["c"]),
+    (Action::Zoom, &["z"]),
+    (Action::Layout, &["L"]),
+    (Action::Filter, &["/"]),
+    (Action::Command, &[":"]),
+    (Action::Help, &["?"]),
+    (Action::Timer, &["t"]),
+    (Action::Sprint, &["s"]),
+    (Action::Analytics, &["a"]),
+    (Action::Forecast, &["f"]),
+    (Action::Tree, &["T"]),
+];
+
+const VIM_PRESET: Preset = &[
+    (Action::Quit, &["q"]),
+    (Action::New, &["n"]),
+    (Action::NewFromTemplate, &["N"]),
+    (Action::Edit, &["e"]),
+    (Action::View, &["v"]),
+    (Action::Delete, &["dd"]),
+    (Action::MoveUp, &["K", "Shift+Up"]),
+    (Action::MoveDown, &["J", "Shift+Down"]),
+    (Action::Left, &["h", "Left"]),
+    (Action::Right, &["l", "Right"]),
+    (Action::Up, &["k", "Up"]),
+    (Action::Down, &["j", "Down"]),
+    (Action::Top, &["gg", "Home"]),
+    (Action::Bottom, &["G", "End"]),
+    (Action::Advance, &["Enter"]),
+    (Action::Scan, &["S"]),
+    (Action::Export, &["x"]),
+    (Action::Density, &["c"]),
+    (Action::Zoom, &["z"]),
+    (Action::Layout, &["L"]),
+    (Action::Filter, &["/"]),
+    (Action::Command, &[":"]),
+    (Action::Help, &["?"]),
+    (Action::Timer, &["t"]),
+    (Action::Sprint, &["s"]),
+    (Action::Analytics, &["a"]),
+    (Action::Forecast, &["f"]),
+    (Action::Tree, &["T"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(preset: &str, bindings: &[(&str, &[&str])]) -> Result<Keymap> {
+        Keymap::from_config(&KeysConfig {
+            preset: Some(preset.to_string()),
+            bindings: bindings
+                .iter()
+                .map(|(action, keys)| {
+                    let keys = keys.iter().map(|k| k.to_string()).collect();
+                    (action.to_string(), keys)
+                })
+                .collect(),
+        })
+    }
+
+    fn keys(s: &str) -> Vec<Key> {
+        KeySequence::parse(s).unwrap().0
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        keymap("default", &[]).unwrap();
+        keymap("vim", &[]).unwrap();
+        assert!(keymap("emacs", &[]).is_err());
+    }
+
+    #[test]
+    fn parses_keys_and_sequences() {
+        assert_eq!(keys("gg"), keys("g g"));
+        assert_eq!(keys("G"), keys("Shift+g"));
+        assert_eq!(keys("Ctrl+s"), keys("control+s"));
+        assert_eq!(
+            keys("+"),
+            vec![Key::new(KeyCode::Char('+'), KeyModifiers::NONE)]
+        );
+        assert_eq!(
+            keys("Space"),
+            vec![Key::new(KeyCode::Char(' '), KeyModifiers::NONE)]
+        );
+        assert_eq!(
+            keys("F5"),
+            vec![Key::new(KeyCode::F(5), KeyModifiers::NONE)]
+        );
+        assert_eq!(keys("öä").len(), 2);
+        assert_eq!(KeySequence::parse("öä").unwrap().to_string(), "öä");
+
+        for bad in ["", "Hyper+x", "Ctrl+", "Nope Enter", "Ctrl+F300"] {
+            assert!(KeySequence::parse(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn rejects_prefix_conflicts() {
+        let err = keymap("vim", &[("bottom", &["g"])]).err().unwrap();
+        assert!(err.to_string().contains("'gg' for 'top'"), "{}", err);
+        // The same keys for two actions
+        assert!(keymap("default", &[("new", &["e"])]).is_err());
+        // Replacing the conflicting binding makes it fine
+        keymap("vim", &[("bottom", &["g"]), ("top", &["Home"])]).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_actions() {
+        assert!(keymap("default", &[("fly", &["F1"])]).is_err());
+    }
+
+    #[test]
+    fn looks_up_sequences() {
+        let keymap = keymap("vim", &[]).unwrap();
+        assert!(matches!(keymap.lookup(&keys("g")), Lookup::Pending));
+        assert!(matches!(
+            keymap.lookup(&keys("gg")),
+            Lookup::Action(Action::Top)
+        ));
+        assert!(matches!(keymap.lookup(&keys("gx")), Lookup::None));
+        assert!(matches!(
+            keymap.lookup(&keys("j")),
+            Lookup::Action(Action::Down)
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! `path:line` references of a task and how to find them again after the
+//! code has moved on.
+
+use crate::app::Task;
+use crate::git::{self, git};
+use anyhow::Result;
+use std::{fs, path::Path};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub path: String,
+    pub line: usize,
+    /// Text expected on the line (scanned comments only).
+    pub text: Option<String>,
+    /// Commit `line` refers to (scanned comments only). `None` when the
+    /// file had uncommitted changes.
+    pub commit: Option<String>,
+    /// How the location was re-resolved, shown in the picker.
+    pub note: Option<String>,
+}
+
+/// The scanned source of `task` followed by every `path:line` mentioned
+/// in its title or description, without duplicates. A mention without a
+/// directory (`host.com:443`) only counts if the file exists under `root`.
+pub fn of_task(task: &Task, root: &Path) -> Vec<Location> {
+    let mut locations = Vec::new();
+    if let Some(source) = &task.source {
+        locations.push(Location {
+            path: source.path.clone(),
+            line: source.line,
+            text: Some(source.text.clone()).filter(|t| !t.is_empty()),
+            commit: source.commit.clone(),
+            note: None,
+        });
+    }
+    let words = task
+        .title
+        .split_whitespace()
+        .chain(task.description.split_whitespace());
+    for word in words {
+        if let Some(location) = parse(word)
+            && (location.path.contains('/') || root.join(&location.path).is_file())
+            && !locations
+                .iter()
+                .any(|l| l.path == location.path && l.line == location.line)
+        {
+            locations.push(location);
+        }
+    }
+    locations
+}
+
+/// `src/main.rs:42` or `src/main.rs:42:7`, possibly wrapped in backticks,
+/// brackets or punctuation.
+fn parse(word: &str) -> Option<Location> {
+    let word = word.trim_matches(|c: char| "`'\"()[]<>,;".contains(c));
+    let mut parts = word.split(':');
+    let path = parts.next()?;
+    let line: usize = parts.next()?.trim_end_matches('.').parse().ok()?;
+    let looks_like_path = (path.contains('.') || path.contains('/'))
+        && !path.contains("//")
+        && path
+            .chars()
+            .all(|c| c.is_alphanumeric() || "._-/".contains(c));
+    (looks_like_path && line > 0).then(|| Location {
+        path: path.trim_start_matches("./").to_string(),
+        line,
+        text: None,
+        commit: None,
+        note: None,
+    })
+}
+
+/// Re-resolves `location` against the working tree at `root`: follows
+/// renames recorded in git, then shifts the line by the diff from the
+/// recorded commit to the working tree and, for scanned comments, by
+/// searching for the text.
+pub fn resolve(root: &Path, location: &Location) -> Location {
+    let mut resolved = location.clone();
+    let mut notes = Vec::new();
+
+    if !root.join(&resolved.path).exists()
+        && let Some(new_path) = follow_rename(root, &resolved.path, resolved.commit.as_deref())
+    {
+        notes.push(format!("moved from {}", resolved.path));
+        resolved.path = new_path;
+    }
+
+    if let Some(commit) = &location.commit
+        && let Ok(line) = shift_line(root, commit, &location.path, &resolved.path, location.line)
+    {
+        resolved.line = line;
+    }
+
+    if let Some(text) = &location.text
+        && let Ok(content) = fs::read_to_string(root.join(&resolved.path))
+    {
+        let lines: Vec<&str> = content.lines().collect();
+        let on_line = lines
+            .get(resolved.line - 1)
+            .is_some_and(|l| l.contains(text));
+        if !on_line
+            && let Some(found) = (0..lines.len())
+                .filter(|&i| lines[i].contains(text))
+                .min_by_key(|&i| (i + 1).abs_diff(resolved.line))
+        {
+            resolved.line = found + 1;
+        }
+    }
+
+    if resolved.line != location.line {
+        notes.push(format!("was line {}", location.line));
+    }
+    if !root.join(&resolved.path).exists() {
+        notes.push("file not found".to_string());
+    }
+    resolved.note = (!notes.is_empty()).then(|| notes.join(", "));
+    if resolved.path != location.path || resolved.line != location.line {
+        resolved.commit = git::head_if_clean(root, &resolved.path);
+    }
+    resolved
+}
+
+/// Follows renames of `path` up to `HEAD`, since `commit` when known or
+/// through the whole history otherwise.
+fn follow_rename(root: &Path, path: &str, commit: Option<&str>) -> Option<String> {
+    let output = match commit {
+        Some(commit) => git(&["diff", "-M", "--name-status", commit, "HEAD"], Some(root)),
+        None => git(
+            &[
+                "log",
+                "-M",
+                "--diff-filter=R",
+                "--name-status",
+                "--format=",
+                "--reverse",
+                "HEAD",
+            ],
+            Some(root),
+        ),
+    }
+    .ok()?;
+
+    let mut current = path.to_string();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let [status, from, to] = fields.as_slice()
+            && status.starts_with('R')
+            && *from == current
+        {
+            current = to.to_string();
+        }
+    }
+    (current != path).then_some(current)
+}
+
+/// Maps `line` of `old_path` at `commit` to the same line of `new_path`
+/// in the working tree using the zero-context diff hunks.
+fn shift_line(
+    root: &Path,
+    commit: &str,
+    old_path: &str,
+    new_path: &str,
+    line: usize,
+) -> Result<usize> {
+    let diff = if old_path == new_path {
+        git(&["diff", "-U0", commit, "--", new_path], Some(root))?
+    } else {
+        git(
+            &["diff", "-M", "-U0", commit, "--", old_path, new_path],
+            Some(root),
+        )?
+    };
+
+    let mut offset: isize = 0;
+    for hunk in diff.lines().filter_map(|l| l.strip_prefix("@@ -")) {
+        let Some((old, rest)) = hunk.split_once(" +") else {
+            continue;
+        };
+        let new = rest.split(' ').next().unwrap_or("");
+        let (old_start, old_len) = parse_range(old);
+        let (new_start, new_len) = parse_range(new);
+
+        // Pure insertions (`-12,0`) go after line 12, so 12 itself stays put
+        if line < old_start || (old_len == 0 && line == old_start) {
+            break;
+        }
+        if line >= old_start + old_len {
+            offset += new_len as isize - old_len as isize;
+        } else {
+            // The line itself was changed; its new block is the best guess
+            return Ok(new_start.max(1));
+        }
+    }
+    Ok(line.saturating_add_signed(offset).max(1))
+}
+
+/// `12,3` -> `(12, 3)`, `12` -> `(12, 1)`
+fn parse_range(range: &str) -> (usize, usize) {
+    match range.split_once(',') {
+        Some((start, len)) => (start.parse().unwrap_or(0), len.parse().unwrap_or(0)),
+        None => (range.parse().unwrap_or(0), 1),
+    }
+}
@@ -1,25 +1,69 @@
 mod app;
+mod cli;
+mod clipboard;
+mod config;
+mod csv;
+mod epics;
+mod export;
+mod external_editor;
+mod flow;
+mod forecast;
+mod git;
+mod importer;
 mod io;
+mod keymap;
+mod locations;
+mod markdown;
+mod modes;
+mod palette;
+mod quick_add;
+mod recurrence;
+mod scan;
+mod sprint;
+mod templates;
+mod theme;
+mod timesheet;
 mod ui;
 
-use crate::app::{App, EditFocus};
+use crate::app::{App, Drag, EditFocus};
+use crate::config::Config;
+use crate::keymap::{Action, Lookup};
+use crate::modes::{Command, Mode};
 use anyhow::Result;
 use ratatui::crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyEvent, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{Terminal, prelude::*};
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+/// How often the header's timer is redrawn while it runs.
+const TIMER_TICK: Duration = Duration::from_secs(1);
 
 fn main() -> Result<()> {
     let data_path = io::find_storage_path()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&data_path, &args);
+    }
+
+    let config = Config::load(&data_path)?;
+    let mut app = App::new(data_path, &config)?;
+
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(data_path);
     let res = run_app(&mut terminal, &mut app);
 
     disable_raw_mode()?;
@@ -36,86 +80,327 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> std::io::Result<()> {
+fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> std::io::Result<()> {
     loop {
         terminal.draw(|f| ui::render(f, app))?;
 
-        if let Event::Key(key) = event::read()? {
-            // 1. Edit Mode (Split Window)
-            if app.edit_mode {
-                match key.code {
-                    KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        app.save_edit_changes()
-                    }
-                    KeyCode::Esc => app.close_edit_mode(),
-                    KeyCode::Tab => app.toggle_edit_focus(),
-
-                    _ => match app.edit_focus {
-                        EditFocus::Title => {
-                            // Block Enter for Title (act as Tab or Submit)
-                            if key.code == KeyCode::Enter {
-                                app.toggle_edit_focus();
-                            } else {
-                                app.title_editor.input(key);
-                            }
-                        }
-                        EditFocus::Description => {
-                            app.description_editor.input(key);
-                        }
-                    },
-                }
+        // Wake up to clear a status message once it times out and to
+        // count up a running timer
+        let timer_tick = app.running_timer().map(|_| TIMER_TICK);
+        if let Some(timeout) = app.status_timeout().into_iter().chain(timer_tick).min()
+            && !event::poll(timeout)?
+        {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) => key,
+            Event::Mouse(mouse) => {
+                handle_mouse(app, mouse);
+                continue;
             }
-            // 2. Quick Add (Footer)
-            else if app.input_mode {
-                match key.code {
-                    KeyCode::Enter => app.submit_input(), // Enter submits
-                    KeyCode::Esc => app.cancel_input(),
-                    _ => {
-                        app.title_editor.input(key);
-                    }
-                }
+            _ => continue,
+        };
+        let mode = app.mode();
+        if mode != Mode::Board {
+            match mode.lookup(key) {
+                Some(command) => run_command(terminal, app, mode, command)?,
+                None if mode.takes_text() => type_text(app, mode, key),
+                None => {}
             }
-            // 3. View Mode
-            else if app.view_mode {
-                match key.code {
-                    KeyCode::Esc | KeyCode::Char('v') | KeyCode::Char('q') | KeyCode::Enter => {
-                        app.close_view_mode()
-                    }
-                    _ => {}
-                }
+        }
+        // Navigation (configurable, see keymap.rs)
+        else {
+            app.pending_keys.push(key.into());
+            let mut lookup = app.keymap.lookup(&app.pending_keys);
+            // The key that breaks off a sequence counts on its own
+            if matches!(lookup, Lookup::None) && app.pending_keys.len() > 1 {
+                app.pending_keys = vec![key.into()];
+                lookup = app.keymap.lookup(&app.pending_keys);
+            }
+            let action = match lookup {
+                Lookup::Action(action) => Some(action),
+                Lookup::Pending => continue,
+                Lookup::None => None,
+            };
+            app.pending_keys.clear();
+
+            match action {
+                Some(Action::Quit) => return Ok(()),
+                Some(action) => dispatch(app, action),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Runs a key of one of the modes on top of the board (see modes.rs).
+fn run_command<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mode: Mode,
+    command: Command,
+) -> std::io::Result<()> {
+    match command {
+        Command::SubmitInput => app.submit_input(),
+        Command::CancelInput => app.cancel_input(),
+        Command::PrevTemplate => app.prev_template(),
+        Command::NextTemplate => app.next_template(),
+        Command::PickTemplate => app.pick_template(),
+        Command::CloseTemplates => app.close_templates(),
+        Command::KeepFilter => app.close_filter(),
+        Command::ClearFilter => app.clear_filter(),
+        Command::RunLine => app.run_palette(),
+        Command::CancelLine => app.close_palette(),
+        Command::CompleteWord => app.complete_palette(),
+        Command::OlderLine => app.palette_history(true),
+        Command::NewerLine => app.palette_history(false),
+        Command::SaveEdit => app.save_edit_changes(),
+        Command::EditExternally => {
+            if let Some(text) = app.external_edit_text() {
+                edit_externally(terminal, app, text)?;
+            }
+        }
+        Command::CancelEdit => app.cancel_edit(),
+        Command::SwitchEditField => app.toggle_edit_focus(),
+        Command::EditEnter => match app.edit_focus {
+            EditFocus::Title => app.toggle_edit_focus(),
+            EditFocus::Description => {
+                app.description_editor.insert_newline();
             }
-            // 4. Delete Confirm
-            else if app.delete_mode {
-                match key.code {
-                    KeyCode::Char('y') | KeyCode::Enter => app.confirm_delete(),
-                    KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => app.cancel_delete(),
-                    _ => {}
+        },
+        Command::DiscardChanges => app.close_edit_mode(),
+        Command::KeepEditing => app.keep_editing(),
+        Command::CloseView => app.close_view_mode(),
+        Command::EditTask => app.open_edit_mode(),
+        Command::DeleteTask => app.prompt_delete(),
+        Command::OpenHelp => app.open_help(),
+        Command::OpenLocations => app.open_locations(),
+        Command::NextCheckbox => app.next_checkbox(),
+        Command::PrevCheckbox => app.prev_checkbox(),
+        Command::ToggleCheckbox => app.toggle_checkbox(),
+        Command::NextChild => app.cycle_child(1),
+        Command::PrevChild => app.cycle_child(-1),
+        Command::OpenChild => app.open_child(),
+        Command::OpenParent => app.open_parent(),
+        Command::ScrollUp | Command::ScrollDown | Command::PageUp | Command::PageDown
+            if mode == Mode::Help =>
+        {
+            app.scroll_help(command)
+        }
+        Command::ScrollUp => app.scroll_view(-1),
+        Command::ScrollDown => app.scroll_view(1),
+        Command::PageUp => app.scroll_view_page(-1),
+        Command::PageDown => app.scroll_view_page(1),
+        Command::ScrollTop => app.scroll_view_home(),
+        Command::ScrollBottom => app.scroll_view_end(),
+        Command::ConfirmDelete => app.confirm_delete(false),
+        Command::DeleteKeepChildren => app.confirm_delete(false),
+        Command::DeleteWithChildren => app.confirm_delete(true),
+        Command::CancelDelete => app.cancel_delete(),
+        Command::PrevLocation => app.prev_location(),
+        Command::NextLocation => app.next_location(),
+        Command::OpenLocation => {
+            if let Some((path, line)) = app.selected_location() {
+                let result = suspend_tui(terminal, || external_editor::open_at(&path, line))?;
+                if let Err(err) = result {
+                    app.flash_error(format!("{:#}", err));
                 }
             }
-            // 5. Navigation
-            else {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Char('n') => app.start_adding(),
-                    KeyCode::Char('e') => app.open_edit_mode(),
-                    KeyCode::Char('v') => app.open_view_mode(),
-                    KeyCode::Char('d') => app.prompt_delete(),
-
-                    KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                        app.move_task_up()
-                    }
-                    KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                        app.move_task_down()
-                    }
-
-                    KeyCode::Left => app.prev_column(),
-                    KeyCode::Right => app.next_column(),
-                    KeyCode::Up => app.prev_item(),
-                    KeyCode::Down => app.next_item(),
-                    KeyCode::Enter => app.move_current_task(),
-                    _ => {}
+        }
+        Command::CloseLocations => app.close_locations(),
+        Command::NextExportFormat => app.next_export_format(),
+        Command::ExportToFile => app.export_to_file(),
+        Command::ExportToClipboard => app.export_to_clipboard(),
+        Command::CloseExport => app.close_export(),
+        Command::RetryEdit => {
+            if let Some(text) = app.take_external_error() {
+                edit_externally(terminal, app, text)?;
+            }
+        }
+        Command::DiscardEdit => {
+            app.take_external_error();
+        }
+        Command::CloseSprint => app.close_sprint(),
+        Command::NextRange => app.next_analytics_range(),
+        Command::CloseAnalytics => app.close_analytics(),
+        Command::MoreItems => app.adjust_forecast(1, 0),
+        Command::FewerItems => app.adjust_forecast(-1, 0),
+        Command::LaterDate => app.adjust_forecast(0, 7),
+        Command::EarlierDate => app.adjust_forecast(0, -7),
+        Command::CloseForecast => app.close_forecast(),
+        Command::TreeUp => app.move_in_tree(-1),
+        Command::TreeDown => app.move_in_tree(1),
+        Command::OpenTreeTask => app.open_tree_task(),
+        Command::CloseTree => app.close_tree(),
+        Command::CloseHelp => app.close_help(),
+    }
+    Ok(())
+}
+
+/// A key without a binding in a mode with a text field.
+fn type_text(app: &mut App, mode: Mode, key: KeyEvent) {
+    match mode {
+        Mode::QuickAdd => {
+            app.title_editor.input(key);
+        }
+        Mode::Filter => {
+            app.filter_editor.input(key);
+            app.update_filter();
+        }
+        Mode::Command => {
+            if let Some(palette) = &mut app.palette {
+                palette.editor.input(key);
+            }
+            app.palette_edited();
+        }
+        Mode::Edit => match app.edit_focus {
+            EditFocus::Title => {
+                app.title_editor.input(key);
+            }
+            EditFocus::Description => {
+                app.description_editor.input(key);
+            }
+        },
+        Mode::Export => {
+            if let Some(dialog) = &mut app.export_dialog {
+                dialog.path.input(key);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Leaves the TUI the same way `main` does on exit, runs `f` (usually an
+/// editor) on the plain terminal, then restores the TUI.
+fn suspend_tui<B: Backend + Write, T>(
+    terminal: &mut Terminal<B>,
+    f: impl FnOnce() -> T,
+) -> std::io::Result<T> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    let result = f();
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+/// Runs `$EDITOR` on `text` and applies the result to the selected task.
+fn edit_externally<B: Backend + Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    text: String,
+) -> std::io::Result<()> {
+    match suspend_tui(terminal, || external_editor::run(&text))? {
+        Ok(edited) => {
+            if let Err(err) = app.apply_external_edit(&edited) {
+                app.external_edit_failed(format!("{:#}", err), edited);
+            }
+        }
+        Err(err) => app.external_edit_failed(format!("{:#}", err), text),
+    }
+    Ok(())
+}
+
+fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    match (app.mode(), mouse.kind) {
+        (Mode::Board, _) => {}
+        (Mode::View, MouseEventKind::ScrollDown) => return app.scroll_view(1),
+        (Mode::View, MouseEventKind::ScrollUp) => return app.scroll_view(-1),
+        (Mode::Help, MouseEventKind::ScrollDown) => return app.scroll_help(Command::ScrollDown),
+        (Mode::Help, MouseEventKind::ScrollUp) => return app.scroll_help(Command::ScrollUp),
+        _ => return,
+    }
+    let hit = app.hit_test(mouse.column, mouse.row);
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let Some((col, item)) = hit else { return };
+            app.select(col, item);
+            let Some(index) = item else { return };
+
+            let now = Instant::now();
+            let double = matches!(app.last_click, Some((at, c, i))
+                if c == col && i == index && now.duration_since(at) < DOUBLE_CLICK);
+            if double {
+                app.last_click = None;
+                app.open_view_mode();
+            } else {
+                app.last_click = Some((now, col, index));
+                app.drag = Some(Drag {
+                    column: col,
+                    index,
+                    over: None,
+                });
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if let Some(drag) = &mut app.drag {
+                drag.over = hit.map(|(col, _)| col);
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            if let (Some(drag), Some((col, item))) = (app.drag.take(), hit) {
+                // A plain click also ends with Up on the same card; ignore that
+                if drag.over.is_some() {
+                    app.drop_task(drag, col, item);
                 }
             }
         }
+        MouseEventKind::ScrollDown => {
+            if let Some((col, _)) = hit {
+                app.scroll_column(col, 1);
+            }
+        }
+        MouseEventKind::ScrollUp => {
+            if let Some((col, _)) = hit {
+                app.scroll_column(col, -1);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn dispatch(app: &mut App, action: Action) {
+    match action {
+        Action::Quit => {}
+        Action::New => app.start_adding(),
+        Action::NewFromTemplate => app.open_templates(),
+        Action::Edit => app.open_edit_mode(),
+        Action::View => app.open_view_mode(),
+        Action::Delete => app.prompt_delete(),
+        Action::MoveUp => app.move_task_up(),
+        Action::MoveDown => app.move_task_down(),
+        Action::Left => app.prev_column(),
+        Action::Right => app.next_column(),
+        Action::Up => app.prev_item(),
+        Action::Down => app.next_item(),
+        Action::Top => app.first_item(),
+        Action::Bottom => app.last_item(),
+        Action::Advance => app.move_current_task(),
+        Action::Scan => app.scan(),
+        Action::Export => app.open_export(),
+        Action::Density => app.toggle_density(),
+        Action::Zoom => app.toggle_zoom(),
+        Action::Layout => app.cycle_layout(),
+        Action::Filter => app.start_filter(),
+        Action::Command => app.open_palette(),
+        Action::Help => app.open_help(),
+        Action::Timer => app.toggle_timer(),
+        Action::Sprint => app.open_sprint(),
+        Action::Analytics => app.open_analytics(),
+        Action::Forecast => app.open_forecast(),
+        Action::Tree => app.open_tree(),
     }
 }
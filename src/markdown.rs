@@ -0,0 +1,396 @@
+//! A small Markdown renderer for task descriptions.
+//!
+//! Works line by line so every rendered checkbox maps back to a single
+//! source line, which is what makes toggling from the view modal simple.
+
+use crate::theme::Theme;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+};
+
+/// Line numbers (0-based) of every `- [ ]` / `- [x]` item, in order.
+pub fn checkbox_lines(source: &str) -> Vec<usize> {
+    let mut in_code = false;
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            if is_fence(line) {
+                in_code = !in_code;
+                return None;
+            }
+            (!in_code && parse_checkbox(line).is_some()).then_some(i)
+        })
+        .collect()
+}
+
+/// `(checked, total)` checkboxes.
+pub fn checkbox_progress(source: &str) -> (usize, usize) {
+    let lines: Vec<&str> = source.lines().collect();
+    let boxes = checkbox_lines(source);
+    let checked = boxes
+        .iter()
+        .filter(|&&i| parse_checkbox(lines[i]).is_some_and(|(_, checked, _)| checked))
+        .count();
+    (checked, boxes.len())
+}
+
+/// Index in the rendered `Text` of source line `line_no` (code fences
+/// produce no line of their own).
+pub fn rendered_line(source: &str, line_no: usize) -> usize {
+    source
+        .lines()
+        .take(line_no)
+        .filter(|line| !is_fence(line))
+        .count()
+}
+
+/// Flips the checkbox on `line_no` and returns the new source.
+pub fn toggle_checkbox(source: &str, line_no: usize) -> String {
+    // Not inside a code block
+    let is_box = checkbox_lines(source).contains(&line_no);
+    let mut lines: Vec<String> = source.lines().map(String::from).collect();
+    if let Some(line) = lines.get_mut(line_no).filter(|_| is_box)
+        && let Some((_, checked, _)) = parse_checkbox(line)
+        && let Some(open) = line.find('[')
+    {
+        let mark = if checked { "[ ]" } else { "[x]" };
+        line.replace_range(open..open + 3, mark);
+    }
+    let mut out = lines.join("\n");
+    if source.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// `source` with every checkbox unchecked.
+pub fn uncheck_all(source: &str) -> String {
+    checkbox_lines(source)
+        .into_iter()
+        .filter(|&i| {
+            source
+                .lines()
+                .nth(i)
+                .and_then(parse_checkbox)
+                .is_some_and(|(_, c, _)| c)
+        })
+        .fold(source.to_string(), |text, i| toggle_checkbox(&text, i))
+}
+
+/// Renders `source` with styles from `theme`. `width` is used to pad code
+/// blocks and rules; `selected` is the line of the highlighted checkbox.
+pub fn render(source: &str, theme: &Theme, width: u16, selected: Option<usize>) -> Text<'static> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for (i, raw) in source.lines().enumerate() {
+        if is_fence(raw) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            let padded = format!(" {:<w$}", raw, w = (width as usize).saturating_sub(1));
+            lines.push(Line::from(Span::styled(padded, theme.code)));
+            continue;
+        }
+
+        let indent = raw.len() - raw.trim_start().len();
+        let trimmed = raw.trim_start();
+        let pad = " ".repeat(indent);
+
+        if let Some((level, text)) = parse_heading(trimmed) {
+            let mut style = theme.heading;
+            if level == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            lines.push(Line::from(inline(text, theme, style)));
+        } else if is_rule(trimmed) {
+            lines.push(Line::styled("─".repeat(width as usize), theme.muted));
+        } else if let Some((_, checked, text)) = parse_checkbox(raw) {
+            let mark = if checked { "☑ " } else { "☐ " };
+            let base = if checked {
+                theme.muted.add_modifier(Modifier::CROSSED_OUT)
+            } else {
+                Style::default()
+            };
+            let mut spans = vec![Span::raw(pad), Span::raw(mark)];
+            spans.extend(inline(text, theme, base));
+            let mut line = Line::from(spans);
+            if selected == Some(i) {
+                line = line.style(theme.selected);
+            }
+            lines.push(line);
+        } else if let Some(text) = parse_bullet(trimmed) {
+            let mut spans = vec![Span::raw(pad), Span::styled("• ", theme.muted)];
+            spans.extend(inline(text, theme, Style::default()));
+            lines.push(Line::from(spans));
+        } else if let Some((number, text)) = parse_numbered(trimmed) {
+            let mut spans = vec![
+                Span::raw(pad),
+                Span::styled(format!("{}. ", number), theme.muted),
+            ];
+            spans.extend(inline(text, theme, Style::default()));
+            lines.push(Line::from(spans));
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            let style = theme.muted.add_modifier(Modifier::ITALIC);
+            let mut spans = vec![Span::styled("│ ", theme.muted)];
+            spans.extend(inline(text.trim_start(), theme, style));
+            lines.push(Line::from(spans));
+        } else {
+            let mut spans = vec![Span::raw(pad)];
+            spans.extend(inline(trimmed, theme, Style::default()));
+            lines.push(Line::from(spans));
+        }
+    }
+    Text::from(lines)
+}
+
+fn is_fence(line: &str) -> bool {
+    let t = line.trim_start();
+    t.starts_with("```") || t.starts_with("~~~")
+}
+
+fn is_rule(line: &str) -> bool {
+    let t: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    t.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|c| t.chars().all(|x| x.to_string() == *c))
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+        Some((level, line[level..].trim()))
+    } else {
+        None
+    }
+}
+
+fn parse_bullet(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "].iter().find_map(|p| line.strip_prefix(p))
+}
+
+fn parse_numbered(line: &str) -> Option<(&str, &str)> {
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let rest = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))?;
+    Some((&line[..digits], rest))
+}
+
+/// `- [ ] text` -> `(indent, checked, text)`
+fn parse_checkbox(line: &str) -> Option<(usize, bool, &str)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = parse_bullet(line.trim_start())?;
+    let (checked, text) = if let Some(text) = rest.strip_prefix("[ ]") {
+        (false, text)
+    } else if let Some(text) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, text)
+    } else {
+        return None;
+    };
+    Some((indent, checked, text.trim_start()))
+}
+
+/// Inline markup: `**bold**`, `*italic*`, `` `code` ``, `[text](url)`, `<url>`.
+fn inline(text: &str, theme: &Theme, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut buf = String::new();
+    let (mut bold, mut italic) = (false, false);
+    let mut i = 0; // byte offset into `text`
+
+    let style = |bold: bool, italic: bool| {
+        let mut s = base;
+        if bold {
+            s = s.add_modifier(Modifier::BOLD);
+        }
+        if italic {
+            s = s.add_modifier(Modifier::ITALIC);
+        }
+        s
+    };
+    let flush = |buf: &mut String, spans: &mut Vec<Span<'static>>, s: Style| {
+        if !buf.is_empty() {
+            spans.push(Span::styled(std::mem::take(buf), s));
+        }
+    };
+
+    while let Some(c) = text[i..].chars().next() {
+        let rest = &text[i..];
+
+        if c == '\\'
+            && let Some(next) = rest[1..].chars().next()
+        {
+            buf.push(next);
+            i += 1 + next.len_utf8();
+        } else if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                flush(&mut buf, &mut spans, style(bold, italic));
+                spans.push(Span::styled(rest[1..1 + end].to_string(), theme.code));
+                i += end + 2;
+            } else {
+                buf.push(c);
+                i += 1;
+            }
+        } else if (rest.starts_with("**") || rest.starts_with("__"))
+            && (bold || rest[2..].contains(&rest[..2]))
+        {
+            flush(&mut buf, &mut spans, style(bold, italic));
+            bold = !bold;
+            i += 2;
+        } else if (c == '*' || c == '_')
+            && (italic
+                // Opens only before text: `**open` and `a * b *` stay literal
+                || (rest[1..].contains(c)
+                    && rest[1..].starts_with(|n: char| n != c && !n.is_whitespace())
+                    && (c == '*'
+                        || text[..i]
+                            .chars()
+                            .next_back()
+                            .is_none_or(|p| !p.is_alphanumeric()))))
+        {
+            flush(&mut buf, &mut spans, style(bold, italic));
+            italic = !italic;
+            i += 1;
+        } else if let Some((label, url, len)) = parse_link(rest) {
+            flush(&mut buf, &mut spans, style(bold, italic));
+            spans.push(Span::styled(label.clone(), theme.link));
+            if label != url {
+                spans.push(Span::styled(format!(" ({})", url), theme.muted));
+            }
+            i += len;
+        } else {
+            buf.push(c);
+            i += c.len_utf8();
+        }
+    }
+    flush(&mut buf, &mut spans, style(bold, italic));
+    spans
+}
+
+/// `[label](url)` or `<scheme://url>` at the start of `s`:
+/// returns `(label, url, bytes consumed)`.
+fn parse_link(s: &str) -> Option<(String, String, usize)> {
+    if let Some(rest) = s.strip_prefix('[') {
+        // The first `]` ends the label; `[a] and [b](u)` links only `b`
+        let close = rest.find(']')?;
+        let label = &rest[..close];
+        let after = rest[close + 1..].strip_prefix('(')?;
+        let end = after.find(')')?;
+        let url = &after[..end];
+        let len = 1 + label.len() + 2 + url.len() + 1;
+        return Some((label.to_string(), url.to_string(), len));
+    }
+    if let Some(rest) = s.strip_prefix('<') {
+        let end = rest.find('>')?;
+        let url = &rest[..end];
+        if url.contains("://") && !url.contains(' ') {
+            return Some((url.to_string(), url.to_string(), url.len() + 2));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn theme() -> Theme {
+        Theme::from_config(&Config::default()).unwrap()
+    }
+
+    /// The text of each span with its style.
+    fn spans(text: &str) -> Vec<(String, Style)> {
+        inline(text, &theme(), Style::default())
+            .into_iter()
+            .map(|s| (s.content.into_owned(), s.style))
+            .collect()
+    }
+
+    fn plain(text: &str) -> String {
+        spans(text).into_iter().map(|(s, _)| s).collect()
+    }
+
+    #[test]
+    fn inline_markup() {
+        let theme = theme();
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let italic = Style::default().add_modifier(Modifier::ITALIC);
+        assert_eq!(
+            spans("a **b** *c* `d`"),
+            vec![
+                ("a ".to_string(), Style::default()),
+                ("b".to_string(), bold),
+                (" ".to_string(), Style::default()),
+                ("c".to_string(), italic),
+                (" ".to_string(), Style::default()),
+                ("d".to_string(), theme.code),
+            ]
+        );
+        assert_eq!(plain("__b__ _i_"), "b i");
+    }
+
+    #[test]
+    fn unmatched_markers_stay_literal() {
+        assert_eq!(plain("2 * 3 = 6"), "2 * 3 = 6");
+        assert_eq!(plain("a ` b"), "a ` b");
+        assert_eq!(plain("**open"), "**open");
+        assert_eq!(plain("snake_case_name"), "snake_case_name");
+        assert_eq!(plain(r"\*not italic\*"), "*not italic*");
+        assert_eq!(plain("trailing \\"), "trailing \\");
+    }
+
+    #[test]
+    fn multibyte_text() {
+        assert_eq!(
+            plain("Größe **fett über** `cöde` é"),
+            "Größe fett über cöde é"
+        );
+        assert_eq!(plain("*schräg*"), "schräg");
+        assert_eq!(plain(r"\é"), "é");
+        assert_eq!(plain("[Link ü](https://x.y/ä)"), "Link ü (https://x.y/ä)");
+        assert_eq!(plain("<https://ö.de> 漢字"), "https://ö.de 漢字");
+    }
+
+    #[test]
+    fn links() {
+        let theme = theme();
+        assert_eq!(
+            spans("[a](u)"),
+            vec![
+                ("a".to_string(), theme.link),
+                (" (u)".to_string(), theme.muted)
+            ]
+        );
+        assert_eq!(spans("[u](u)"), vec![("u".to_string(), theme.link)]);
+        assert_eq!(plain("[a] and [b](u)"), "[a] and b (u)");
+        assert_eq!(plain("[a] (u)"), "[a] (u)");
+        assert_eq!(plain("[a](u"), "[a](u");
+        assert_eq!(plain("<not a url>"), "<not a url>");
+    }
+
+    #[test]
+    fn checkboxes_round_trip() {
+        let source = "- [ ] one\n```\n- [ ] in code\n```\n  - [x] two\n";
+        assert_eq!(checkbox_lines(source), vec![0, 4]);
+        assert_eq!(checkbox_progress(source), (1, 2));
+        assert_eq!(rendered_line(source, 4), 2);
+
+        let toggled = toggle_checkbox(source, 0);
+        assert_eq!(toggled, source.replacen("[ ]", "[x]", 1));
+        assert_eq!(toggle_checkbox(&toggled, 0), source);
+        assert_eq!(checkbox_progress(&uncheck_all(&toggled)), (0, 2));
+        // Not a checkbox: unchanged
+        assert_eq!(toggle_checkbox(source, 2), source);
+    }
+}
@@ -0,0 +1,364 @@
+//! The modes that sit on top of the board and their fixed key bindings.
+//!
+//! Open modes form a stack (`App::modes`): a modal can open another one
+//! and gets the keys back once it closes. `run_app` dispatches keys for
+//! the innermost mode through these tables and the `?` overlay lists
+//! them, so the help always matches what the keys do. Board keys are
+//! configurable and live in `keymap.rs`.
+
+use crate::keymap::Key;
+use ratatui::crossterm::event::KeyEvent;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Board,
+    QuickAdd,
+    Templates,
+    Filter,
+    Command,
+    Edit,
+    Discard,
+    View,
+    Delete,
+    DeleteEpic,
+    Locations,
+    Export,
+    EditorError,
+    Sprint,
+    Analytics,
+    Forecast,
+    Tree,
+    Help,
+}
+
+impl Mode {
+    /// Order of the sections in the help overlay.
+    pub const ALL: [Mode; 18] = [
+        Mode::Board,
+        Mode::QuickAdd,
+        Mode::Templates,
+        Mode::Filter,
+        Mode::Command,
+        Mode::Edit,
+        Mode::Discard,
+        Mode::View,
+        Mode::Delete,
+        Mode::DeleteEpic,
+        Mode::Locations,
+        Mode::Export,
+        Mode::EditorError,
+        Mode::Sprint,
+        Mode::Analytics,
+        Mode::Forecast,
+        Mode::Tree,
+        Mode::Help,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Mode::Board => "Board",
+            Mode::QuickAdd => "Quick add",
+            Mode::Templates => "New from template",
+            Mode::Filter => "Filter",
+            Mode::Command => "Command line",
+            Mode::Edit => "Edit",
+            Mode::Discard => "Discard changes",
+            Mode::View => "View",
+            Mode::Delete => "Delete confirm",
+            Mode::DeleteEpic => "Delete epic",
+            Mode::Locations => "Locations",
+            Mode::Export => "Export",
+            Mode::EditorError => "$EDITOR error",
+            Mode::Sprint => "Sprint",
+            Mode::Analytics => "Analytics",
+            Mode::Forecast => "Forecast",
+            Mode::Tree => "Task tree",
+            Mode::Help => "Help",
+        }
+    }
+
+    /// Fixed bindings of the mode (empty for the configurable board).
+    pub fn bindings(self) -> &'static [Binding] {
+        match self {
+            Mode::Board => &[],
+            Mode::QuickAdd => QUICK_ADD,
+            Mode::Templates => TEMPLATES,
+            Mode::Filter => FILTER,
+            Mode::Command => COMMAND,
+            Mode::Edit => EDIT,
+            Mode::Discard => DISCARD,
+            Mode::View => VIEW,
+            Mode::Delete => DELETE,
+            Mode::DeleteEpic => DELETE_EPIC,
+            Mode::Locations => LOCATIONS,
+            Mode::Export => EXPORT,
+            Mode::EditorError => EDITOR_ERROR,
+            Mode::Sprint => SPRINT,
+            Mode::Analytics => ANALYTICS,
+            Mode::Forecast => FORECAST,
+            Mode::Tree => TREE,
+            Mode::Help => HELP,
+        }
+    }
+
+    /// Unbound keys are typed into a text field.
+    pub fn takes_text(self) -> bool {
+        matches!(
+            self,
+            Mode::QuickAdd | Mode::Filter | Mode::Command | Mode::Edit | Mode::Export
+        )
+    }
+
+    pub fn lookup(self, event: KeyEvent) -> Option<Command> {
+        let key = Key::from(event);
+        self.bindings()
+            .iter()
+            .find(|b| {
+                b.keys
+                    .iter()
+                    .any(|k| Key::parse(k).is_ok_and(|parsed| parsed == key))
+            })
+            .map(|b| b.command)
+    }
+}
+
+/// What a key does inside a mode. Run by `run_command` in `main.rs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    SubmitInput,
+    CancelInput,
+    PrevTemplate,
+    NextTemplate,
+    PickTemplate,
+    CloseTemplates,
+    KeepFilter,
+    ClearFilter,
+    RunLine,
+    CancelLine,
+    CompleteWord,
+    OlderLine,
+    NewerLine,
+    SaveEdit,
+    EditExternally,
+    CancelEdit,
+    SwitchEditField,
+    /// Next field from the title, a line break in the description.
+    EditEnter,
+    DiscardChanges,
+    KeepEditing,
+    CloseView,
+    EditTask,
+    DeleteTask,
+    OpenHelp,
+    OpenLocations,
+    NextCheckbox,
+    PrevCheckbox,
+    ToggleCheckbox,
+    NextChild,
+    PrevChild,
+    OpenChild,
+    OpenParent,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    ScrollTop,
+    ScrollBottom,
+    ConfirmDelete,
+    DeleteKeepChildren,
+    DeleteWithChildren,
+    CancelDelete,
+    PrevLocation,
+    NextLocation,
+    OpenLocation,
+    CloseLocations,
+    NextExportFormat,
+    ExportToFile,
+    ExportToClipboard,
+    CloseExport,
+    RetryEdit,
+    DiscardEdit,
+    CloseSprint,
+    /// Cycles the days covered by the cumulative flow diagram.
+    NextRange,
+    CloseAnalytics,
+    MoreItems,
+    FewerItems,
+    LaterDate,
+    EarlierDate,
+    CloseForecast,
+    TreeUp,
+    TreeDown,
+    OpenTreeTask,
+    CloseTree,
+    CloseHelp,
+}
+
+pub struct Binding {
+    /// Same syntax as the config file (`Ctrl+s`, `PageUp`, `G`).
+    pub keys: &'static [&'static str],
+    pub command: Command,
+    pub help: &'static str,
+}
+
+impl Binding {
+    /// Keys as the board section of the help shows them (`↑/k`).
+    pub fn display_keys(&self) -> String {
+        self.keys
+            .iter()
+            .map(|k| Key::parse(k).map_or(k.to_string(), |key| key.to_string()))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+const fn bind(keys: &'static [&'static str], command: Command, help: &'static str) -> Binding {
+    Binding {
+        keys,
+        command,
+        help,
+    }
+}
+
+const QUICK_ADD: &[Binding] = &[
+    bind(&["Enter"], Command::SubmitInput, "Add the task"),
+    bind(&["Esc"], Command::CancelInput, "Cancel"),
+];
+
+const TEMPLATES: &[Binding] = &[
+    bind(&["Up", "k"], Command::PrevTemplate, "Previous"),
+    bind(&["Down", "j"], Command::NextTemplate, "Next"),
+    bind(&["Enter"], Command::PickTemplate, "Use the template"),
+    bind(&["Esc", "q"], Command::CloseTemplates, "Cancel"),
+];
+
+const FILTER: &[Binding] = &[
+    bind(&["Enter"], Command::KeepFilter, "Keep the filter"),
+    bind(&["Esc"], Command::ClearFilter, "Clear the filter"),
+];
+
+const COMMAND: &[Binding] = &[
+    bind(&["Enter"], Command::RunLine, "Run"),
+    bind(&["Esc"], Command::CancelLine, "Cancel"),
+    bind(&["Tab"], Command::CompleteWord, "Complete"),
+    bind(&["Up"], Command::OlderLine, "Previous command"),
+    bind(&["Down"], Command::NewerLine, "Next command"),
+];
+
+const EDIT: &[Binding] = &[
+    bind(&["Ctrl+s"], Command::SaveEdit, "Save"),
+    bind(&["Ctrl+e"], Command::EditExternally, "Open in $EDITOR"),
+    bind(&["Ctrl+o"], Command::OpenLocations, "Linked locations"),
+    bind(&["Esc"], Command::CancelEdit, "Cancel"),
+    bind(&["Tab"], Command::SwitchEditField, "Switch field"),
+    bind(
+        &["Enter"],
+        Command::EditEnter,
+        "Next field (title), new line (description)",
+    ),
+];
+
+const DISCARD: &[Binding] = &[
+    bind(&["y", "Enter"], Command::DiscardChanges, "Discard"),
+    bind(&["n", "q", "Esc"], Command::KeepEditing, "Keep editing"),
+];
+
+const VIEW: &[Binding] = &[
+    bind(&["Esc", "v", "q", "Enter"], Command::CloseView, "Close"),
+    bind(&["e"], Command::EditTask, "Edit"),
+    bind(&["E"], Command::EditExternally, "Open in $EDITOR"),
+    bind(&["d"], Command::DeleteTask, "Delete"),
+    bind(&["o"], Command::OpenLocations, "Linked locations"),
+    bind(&["Tab"], Command::NextCheckbox, "Next checkbox"),
+    bind(&["BackTab"], Command::PrevCheckbox, "Previous checkbox"),
+    bind(&["Space", "x"], Command::ToggleCheckbox, "Toggle checkbox"),
+    bind(&["c"], Command::NextChild, "Next child task"),
+    bind(&["C"], Command::PrevChild, "Previous child task"),
+    bind(&["Right", "l"], Command::OpenChild, "Open the child task"),
+    bind(&["Left", "h"], Command::OpenParent, "Open the epic"),
+    bind(&["Up", "k"], Command::ScrollUp, "Scroll up"),
+    bind(&["Down", "j"], Command::ScrollDown, "Scroll down"),
+    bind(&["PageUp"], Command::PageUp, "Page up"),
+    bind(&["PageDown"], Command::PageDown, "Page down"),
+    bind(&["Home", "g"], Command::ScrollTop, "Top"),
+    bind(&["End", "G"], Command::ScrollBottom, "Bottom"),
+    bind(&["?"], Command::OpenHelp, "Keys"),
+];
+
+const DELETE: &[Binding] = &[
+    bind(&["y", "Enter"], Command::ConfirmDelete, "Delete"),
+    bind(&["n", "q", "Esc"], Command::CancelDelete, "Keep"),
+];
+
+const DELETE_EPIC: &[Binding] = &[
+    bind(
+        &["k", "Enter"],
+        Command::DeleteKeepChildren,
+        "Delete, move the children up a level",
+    ),
+    bind(
+        &["a"],
+        Command::DeleteWithChildren,
+        "Delete with every task below it",
+    ),
+    bind(&["n", "q", "Esc"], Command::CancelDelete, "Keep"),
+];
+
+const LOCATIONS: &[Binding] = &[
+    bind(&["Up", "k"], Command::PrevLocation, "Previous"),
+    bind(&["Down", "j"], Command::NextLocation, "Next"),
+    bind(&["Enter"], Command::OpenLocation, "Open in $EDITOR"),
+    bind(&["Esc", "q"], Command::CloseLocations, "Back"),
+];
+
+const EXPORT: &[Binding] = &[
+    bind(&["Tab"], Command::NextExportFormat, "Next format"),
+    bind(&["Enter"], Command::ExportToFile, "Save to the file"),
+    bind(
+        &["Ctrl+y"],
+        Command::ExportToClipboard,
+        "Copy to the clipboard",
+    ),
+    bind(&["Esc"], Command::CloseExport, "Cancel"),
+];
+
+const EDITOR_ERROR: &[Binding] = &[
+    bind(&["Enter", "e"], Command::RetryEdit, "Edit again"),
+    bind(&["Esc", "q"], Command::DiscardEdit, "Discard"),
+];
+
+const SPRINT: &[Binding] = &[
+    bind(&["Esc", "s", "q"], Command::CloseSprint, "Close"),
+    bind(&["?"], Command::OpenHelp, "Help"),
+];
+
+const ANALYTICS: &[Binding] = &[
+    bind(&["Tab"], Command::NextRange, "14, 30 or 90 days"),
+    bind(&["Esc", "a", "q"], Command::CloseAnalytics, "Close"),
+    bind(&["?"], Command::OpenHelp, "Help"),
+];
+
+const FORECAST: &[Binding] = &[
+    bind(&["Up", "k"], Command::MoreItems, "One task more"),
+    bind(&["Down", "j"], Command::FewerItems, "One task less"),
+    bind(&["Right", "l"], Command::LaterDate, "A week later"),
+    bind(&["Left", "h"], Command::EarlierDate, "A week earlier"),
+    bind(&["Esc", "f", "q"], Command::CloseForecast, "Close"),
+    bind(&["?"], Command::OpenHelp, "Help"),
+];
+
+const TREE: &[Binding] = &[
+    bind(&["Up", "k"], Command::TreeUp, "Previous"),
+    bind(&["Down", "j"], Command::TreeDown, "Next"),
+    bind(&["Enter"], Command::OpenTreeTask, "Go to the task"),
+    bind(&["Esc", "T", "q"], Command::CloseTree, "Close"),
+    bind(&["?"], Command::OpenHelp, "Help"),
+];
+
+const HELP: &[Binding] = &[
+    bind(&["Up", "k"], Command::ScrollUp, "Scroll up"),
+    bind(&["Down", "j"], Command::ScrollDown, "Scroll down"),
+    bind(&["PageUp"], Command::PageUp, "Page up"),
+    bind(&["PageDown"], Command::PageDown, "Page down"),
+    bind(&["Esc", "?", "q"], Command::CloseHelp, "Close"),
+];
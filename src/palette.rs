@@ -0,0 +1,302 @@
+//! The `:` command line: parsing, tab completion and the history that is
+//! kept beside the task file.
+
+use crate::app::{Status, Task};
+use crate::export::Format;
+use crate::quick_add::parse_task_id;
+use anyhow::{Context, Result, bail};
+use std::{cmp::Ordering, fs, path::Path, path::PathBuf, str::FromStr};
+
+const COMMANDS: [&str; 8] = [
+    "move", "tag", "sort", "board", "export", "archive", "sprint", "parent",
+];
+const COLUMNS: [&str; 3] = ["todo", "doing", "done"];
+/// Older entries are dropped when the history is saved.
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, PartialEq)]
+pub enum PaletteCommand {
+    /// `move done [#id]`
+    Move { status: Status, task: Option<u64> },
+    /// `tag +bug -wip [#id]`; a bare word is added.
+    Tag {
+        add: Vec<String>,
+        remove: Vec<String>,
+        task: Option<u64>,
+    },
+    /// `sort priority`: reorders the active column.
+    Sort(SortKey),
+    /// `board bugs` shows the saved view `bugs` from the config, a bare
+    /// `board` the whole board again.
+    Board(Option<String>),
+    /// `export md out.md`; the file defaults to `kanban.<ext>`.
+    Export { format: Format, path: String },
+    /// `archive done`: moves a column to the archive file.
+    Archive(Status),
+    /// `sprint s12 [#id]` plans the task into a sprint, `sprint -` takes it
+    /// out and a bare `sprint` uses the running one.
+    Sprint {
+        name: Option<String>,
+        task: Option<u64>,
+    },
+    /// `parent 12 [#id]` puts the task below #12, `parent -` makes it
+    /// top-level again.
+    Parent {
+        parent: Option<u64>,
+        task: Option<u64>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortKey {
+    Priority,
+    Due,
+    Title,
+    Created,
+    Updated,
+    Id,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 6] = [
+        SortKey::Priority,
+        SortKey::Due,
+        SortKey::Title,
+        SortKey::Created,
+        SortKey::Updated,
+        SortKey::Id,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Priority => "priority",
+            SortKey::Due => "due",
+            SortKey::Title => "title",
+            SortKey::Created => "created",
+            SortKey::Updated => "updated",
+            SortKey::Id => "id",
+        }
+    }
+
+    /// Most urgent, soonest due, A-Z, oldest, most recently updated and
+    /// lowest ID first. Tasks without a priority or due date go last.
+    pub fn compare(self, a: &Task, b: &Task) -> Ordering {
+        fn missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        match self {
+            SortKey::Priority => missing_last(a.priority, b.priority),
+            SortKey::Due => missing_last(a.due, b.due),
+            SortKey::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortKey::Created => a.created_at.cmp(&b.created_at),
+            SortKey::Updated => b.last_update().cmp(&a.last_update()),
+            SortKey::Id => a.id.cmp(&b.id),
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        SortKey::ALL
+            .into_iter()
+            .find(|k| k.name() == s.to_ascii_lowercase())
+            .with_context(|| {
+                let names: Vec<&str> = SortKey::ALL.iter().map(|k| k.name()).collect();
+                format!("Unknown sort key '{}' (expected {})", s, names.join(", "))
+            })
+    }
+}
+
+pub fn parse(line: &str) -> Result<PaletteCommand> {
+    let line = line.trim().trim_start_matches(':');
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&name, args)) = words.split_first() else {
+        bail!("Empty command");
+    };
+    match (name, args) {
+        ("move", args) => {
+            let (task, args) = task_arg(args)?;
+            let [status] = args[..] else {
+                bail!("Usage: move <todo|doing|done> [#id]");
+            };
+            Ok(PaletteCommand::Move {
+                status: status.parse()?,
+                task,
+            })
+        }
+        ("tag", args) => {
+            let (task, args) = task_arg(args)?;
+            if args.is_empty() {
+                bail!("Usage: tag +tag -tag [#id]");
+            }
+            let mut add = Vec::new();
+            let mut remove = Vec::new();
+            for word in args {
+                match word.strip_prefix('-') {
+                    Some(tag) => remove.push(tag.to_string()),
+                    None => add.push(word.trim_start_matches('+').to_string()),
+                }
+            }
+            Ok(PaletteCommand::Tag { add, remove, task })
+        }
+        ("sort", [key]) => Ok(PaletteCommand::Sort(key.parse()?)),
+        ("sort", _) => bail!("Usage: sort <priority|due|title|created|updated|id>"),
+        ("board", []) => Ok(PaletteCommand::Board(None)),
+        ("board", [name]) => Ok(PaletteCommand::Board(Some(name.to_string()))),
+        ("board", _) => bail!("Usage: board [name]"),
+        ("export", [format, rest @ ..]) if rest.len() <= 1 => {
+            let format: Format = format.parse()?;
+            let path = match rest {
+                [path] => path.to_string(),
+                _ => format!("kanban.{}", format.extension()),
+            };
+            Ok(PaletteCommand::Export { format, path })
+        }
+        ("export", _) => bail!("Usage: export <md|csv|html|json> [file]"),
+        ("archive", [status]) => Ok(PaletteCommand::Archive(status.parse()?)),
+        ("archive", _) => bail!("Usage: archive <todo|doing|done>"),
+        ("sprint", args) => {
+            let (task, args) = task_arg(args)?;
+            let name = match args[..] {
+                [] => None,
+                [name] => Some(name.to_string()),
+                _ => bail!("Usage: sprint [name|-] [#id]"),
+            };
+            Ok(PaletteCommand::Sprint { name, task })
+        }
+        ("parent", args) => {
+            let (task, args) = task_arg(args)?;
+            let parent = match args[..] {
+                ["-"] => None,
+                [id] => {
+                    Some(parse_task_id(id).with_context(|| format!("Invalid parent '{}'", id))?)
+                }
+                _ => bail!("Usage: parent <id|-> [#id]"),
+            };
+            Ok(PaletteCommand::Parent { parent, task })
+        }
+        (other, _) => bail!(
+            "Unknown command '{}' (expected {})",
+            other,
+            COMMANDS.join(", ")
+        ),
+    }
+}
+
+/// Splits off a `#id` argument.
+fn task_arg<'a>(args: &'a [&'a str]) -> Result<(Option<u64>, Vec<&'a str>)> {
+    let mut task = None;
+    let mut rest = Vec::new();
+    for &word in args {
+        match word.strip_prefix('#') {
+            Some(id) => {
+                let id = id
+                    .parse()
+                    .with_context(|| format!("Invalid task ID '{}'", word))?;
+                task = Some(id);
+            }
+            None => rest.push(word),
+        }
+    }
+    Ok((task, rest))
+}
+
+/// Where the word being typed at the end of `line` starts, and what it
+/// could be: command names, columns, sort keys, formats, tags, sprints or
+/// `#id`s.
+pub fn complete(line: &str, tasks: &[Task], boards: &[String]) -> (usize, Vec<String>) {
+    let start = line
+        .char_indices()
+        .rfind(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let word = &line[start..];
+    let before: Vec<&str> = line[..start].split_whitespace().collect();
+
+    let words = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let candidates = match before.as_slice() {
+        [] => words(&COMMANDS),
+        [cmd, ..]
+            if word.starts_with('#') && matches!(*cmd, "move" | "tag" | "sprint" | "parent") =>
+        {
+            tasks.iter().map(|t| format!("#{}", t.id)).collect()
+        }
+        ["move" | "archive"] => words(&COLUMNS),
+        ["sort"] => SortKey::ALL.iter().map(|k| k.name().to_string()).collect(),
+        ["board"] => boards.to_vec(),
+        ["export"] => Format::ALL
+            .iter()
+            .map(|f| f.extension().to_string())
+            .collect(),
+        ["export", format] => match format.parse::<Format>() {
+            Ok(format) => vec![format!("kanban.{}", format.extension())],
+            Err(_) => Vec::new(),
+        },
+        ["sprint"] => {
+            let mut names: Vec<String> = tasks.iter().filter_map(|t| t.sprint.clone()).collect();
+            names.sort();
+            names.dedup();
+            names.push("-".to_string());
+            names
+        }
+        ["parent"] => {
+            let mut ids: Vec<String> = tasks.iter().map(|t| t.id.to_string()).collect();
+            ids.push("-".to_string());
+            ids
+        }
+        ["tag", ..] => {
+            let sign = match word.chars().next() {
+                Some('+') => "+",
+                Some('-') => "-",
+                _ => "",
+            };
+            let mut tags: Vec<String> = tasks
+                .iter()
+                .flat_map(|t| &t.tags)
+                .map(|tag| format!("{}{}", sign, tag))
+                .collect();
+            tags.sort();
+            tags.dedup();
+            tags
+        }
+        _ => Vec::new(),
+    };
+    let word = word.to_lowercase();
+    let matching = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&word))
+        .collect();
+    (start, matching)
+}
+
+/// `.git/git-kanban.history` or `.kanban.history`, one command per line.
+pub fn history_path(data_path: &Path) -> PathBuf {
+    data_path.with_extension("history")
+}
+
+/// A missing history file is an empty history.
+pub fn load_history(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Appends `line` unless it repeats the last entry, and writes the file.
+pub fn record(history: &mut Vec<String>, line: &str, path: &Path) -> Result<()> {
+    if history.last().is_some_and(|last| last == line) {
+        return Ok(());
+    }
+    history.push(line.to_string());
+    if history.len() > HISTORY_LIMIT {
+        history.drain(..history.len() - HISTORY_LIMIT);
+    }
+    let mut text = history.join("\n");
+    text.push('\n');
+    fs::write(path, text).with_context(|| format!("Cannot write {}", path.display()))
+}
@@ -0,0 +1,246 @@
+//! Metadata typed inline in quick add:
+//!
+//! ```text
+//! Fix login redirect #bug #auth !p1 @alice due:fri est:3
+//! Water the plants repeat:mon,thu
+//! Migrate the database est:5h sprint:s12
+//! Write the release notes parent:12
+//! ```
+//!
+//! Recognised words are removed from the title. A word that looks like a
+//! token but does not parse stays in the title and is reported. `#12` is a
+//! reference to a task or issue, not a tag, and stays in the title too.
+
+use crate::app::{Task, parse_priority};
+use crate::recurrence::Recurrence;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Parsed {
+    pub title: String,
+    pub tags: Vec<String>,
+    pub priority: Option<u8>,
+    pub assignee: Option<String>,
+    pub due: Option<NaiveDate>,
+    pub estimate: Option<u32>,
+    pub repeat: Option<Recurrence>,
+    pub sprint: Option<String>,
+    /// ID of the epic.
+    pub parent: Option<u64>,
+    /// Words that looked like tokens but did not parse, with the reason.
+    pub problems: Vec<String>,
+}
+
+impl Parsed {
+    /// Copies the parsed fields onto a new task.
+    pub fn apply(self, task: &mut Task) {
+        task.title = self.title;
+        task.tags = self.tags;
+        task.priority = self.priority;
+        task.assignee = self.assignee;
+        task.due = self.due;
+        task.estimate = self.estimate;
+        task.repeat = self.repeat;
+        task.sprint = self.sprint;
+        task.parent = self.parent;
+    }
+}
+
+pub fn parse(input: &str, today: NaiveDate) -> Parsed {
+    let mut parsed = Parsed::default();
+    let mut title = Vec::new();
+    for word in input.split_whitespace() {
+        match token(&mut parsed, word, today) {
+            Ok(true) => {}
+            Ok(false) => title.push(word),
+            Err(e) => {
+                parsed.problems.push(format!("{}: {}", word, e));
+                title.push(word);
+            }
+        }
+    }
+    parsed.title = title.join(" ");
+    parsed
+}
+
+/// Applies `word` if it is a token; `Ok(false)` for ordinary words.
+fn token(parsed: &mut Parsed, word: &str, today: NaiveDate) -> Result<bool> {
+    let is_tag = |t: &&str| !t.is_empty() && !t.bytes().all(|b| b.is_ascii_digit());
+    if let Some(tag) = word.strip_prefix('#').filter(is_tag) {
+        if !parsed.tags.iter().any(|t| t == tag) {
+            parsed.tags.push(tag.to_string());
+        }
+    } else if let Some(p) = word.strip_prefix('!').filter(|p| !p.is_empty()) {
+        parsed.priority = Some(parse_priority(p)?);
+    } else if let Some(name) = word.strip_prefix('@').filter(|n| !n.is_empty()) {
+        parsed.assignee = Some(name.to_string());
+    } else if let Some(date) = word.strip_prefix("due:") {
+        parsed.due = Some(parse_due(date, today)?);
+    } else if let Some(estimate) = word.strip_prefix("est:") {
+        // The unit is set in the config, `3pt` and `3h` read as 3
+        let number = estimate
+            .strip_suffix("pt")
+            .or_else(|| estimate.strip_suffix('h'))
+            .unwrap_or(estimate);
+        let estimate = number
+            .parse()
+            .map_err(|_| anyhow!("expected a whole number of points or hours"))?;
+        parsed.estimate = Some(estimate);
+    } else if let Some(rule) = word.strip_prefix("repeat:") {
+        parsed.repeat = Some(rule.parse()?);
+    } else if let Some(name) = word.strip_prefix("sprint:").filter(|n| !n.is_empty()) {
+        parsed.sprint = Some(name.to_string());
+    } else if let Some(id) = word.strip_prefix("parent:") {
+        parsed.parent = Some(parse_task_id(id)?);
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// `12` or `#12`.
+pub fn parse_task_id(s: &str) -> Result<u64> {
+    s.trim_start_matches('#')
+        .parse()
+        .ok()
+        .filter(|&id| id > 0)
+        .ok_or_else(|| anyhow!("expected a task ID like 12"))
+}
+
+/// `2026-01-31`, `today`, `tomorrow`, a weekday (`fri`, `friday`: the next
+/// one after today) or an offset (`3d`, `2w`).
+pub fn parse_due(s: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let s = s.to_ascii_lowercase();
+    if let Ok(date) = s.parse::<NaiveDate>() {
+        return Ok(date);
+    }
+    match s.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" | "tom" => return Ok(today + Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(weekday) = s.parse::<Weekday>() {
+        let ahead =
+            (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday() - 1) % 7
+                + 1;
+        return Ok(today + Duration::days(ahead as i64));
+    }
+    let number = |suffix: char| s.strip_suffix(suffix)?.parse::<i64>().ok();
+    let days = match (number('d'), number('w')) {
+        (Some(n), _) => Some(n),
+        (None, Some(n)) => n.checked_mul(7),
+        (None, None) => {
+            return Err(anyhow!(
+                "expected YYYY-MM-DD, today, tomorrow, a weekday, 3d or 2w"
+            ));
+        }
+    };
+    days.and_then(Duration::try_days)
+        .and_then(|d| today.checked_add_signed(d))
+        .ok_or_else(|| anyhow!("too far from today"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sunday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn takes_tokens_out_of_the_title() {
+        let parsed = parse(
+            "Fix login #bug #auth #bug !p1 @alice due:fri est:3h repeat:mon,thu sprint:s12 parent:#7 redirect",
+            today(),
+        );
+        assert_eq!(
+            parsed,
+            Parsed {
+                title: "Fix login redirect".to_string(),
+                tags: vec!["bug".to_string(), "auth".to_string()],
+                priority: Some(1),
+                assignee: Some("alice".to_string()),
+                due: Some(date(2026, 10, 23)),
+                estimate: Some(3),
+                repeat: Some("mon,thu".parse().unwrap()),
+                sprint: Some("s12".to_string()),
+                parent: Some(7),
+                problems: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn numbers_after_a_hash_are_references() {
+        let parsed = parse("Crash from #12 #bug #v2 #", today());
+        assert_eq!(parsed.title, "Crash from #12 #");
+        assert_eq!(parsed.tags, vec!["bug", "v2"]);
+    }
+
+    #[test]
+    fn bad_tokens_stay_and_are_reported() {
+        let parsed = parse(
+            "Call !urgent due:someday est:lots parent:0 repeat:often",
+            today(),
+        );
+        assert_eq!(
+            parsed.title,
+            "Call !urgent due:someday est:lots parent:0 repeat:often"
+        );
+        assert_eq!(parsed.problems.len(), 5);
+        assert!(parsed.problems[1].starts_with("due:someday: "));
+        // Bare markers are ordinary words
+        let parsed = parse("a ! @ b", today());
+        assert_eq!(parsed.title, "a ! @ b");
+        assert!(parsed.problems.is_empty());
+    }
+
+    #[test]
+    fn multibyte_words() {
+        let parsed = parse("Grüße an @zoë #ünï-cödé 漢字 due:2026-12-24", today());
+        assert_eq!(parsed.title, "Grüße an 漢字");
+        assert_eq!(parsed.assignee.as_deref(), Some("zoë"));
+        assert_eq!(parsed.tags, vec!["ünï-cödé"]);
+        assert_eq!(parsed.due, Some(date(2026, 12, 24)));
+        assert!(parse("due:fréi", today()).problems.len() == 1);
+    }
+
+    #[test]
+    fn due_dates() {
+        let today = today();
+        assert_eq!(parse_due("today", today).unwrap(), today);
+        assert_eq!(parse_due("TOM", today).unwrap(), date(2026, 10, 19));
+        // The next one, never today
+        assert_eq!(parse_due("sun", today).unwrap(), date(2026, 10, 25));
+        assert_eq!(parse_due("monday", today).unwrap(), date(2026, 10, 19));
+        assert_eq!(parse_due("3d", today).unwrap(), date(2026, 10, 21));
+        assert_eq!(parse_due("-1d", today).unwrap(), date(2026, 10, 17));
+        assert_eq!(parse_due("2w", today).unwrap(), date(2026, 11, 1));
+        for bad in [
+            "",
+            "d",
+            "soon",
+            "2026-02-30",
+            "100000000d",
+            "9223372036854775807w",
+        ] {
+            assert!(parse_due(bad, today).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn task_ids() {
+        assert_eq!(parse_task_id("12").unwrap(), 12);
+        assert_eq!(parse_task_id("#12").unwrap(), 12);
+        for bad in ["0", "#", "-3", "x"] {
+            assert!(parse_task_id(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Recurring tasks. A rule is written the same way in quick add
+//! (`repeat:mon,thu`), the front matter and the task file:
+//!
+//! - `daily`, `weekly`, `monthly`: the next one after the due date
+//! - `mon,thu`: the next of these weekdays
+//! - `3d`, `2w`: that long after the task is done
+
+use anyhow::{Result, bail};
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Longest `3d`/`2w` rule, ten years.
+const MAX_DAYS: u32 = 3660;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    Daily,
+    /// On these weekdays, or on the due date's weekday when empty.
+    Weekly(Vec<Weekday>),
+    /// On the due date's day of the month, or the month's last day.
+    Monthly,
+    /// This many days after completion.
+    AfterDays(u32),
+}
+
+impl Recurrence {
+    /// The due date of the next copy of a task completed on `done`.
+    /// Calendar rules count from the due date (or `done` without one) and
+    /// skip the dates that have already passed. `None` past the last date
+    /// chrono can represent.
+    pub fn next_due(&self, due: Option<NaiveDate>, done: NaiveDate) -> Option<NaiveDate> {
+        let start = due.unwrap_or(done);
+        let mut date = start;
+        match self {
+            Recurrence::AfterDays(days) => return done.checked_add_days(Days::new(*days as u64)),
+            Recurrence::Weekly(days) if !days.is_empty() => {
+                date = date.max(done);
+                loop {
+                    date = date.checked_add_days(Days::new(1))?;
+                    if days.contains(&date.weekday()) {
+                        return Some(date);
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut months = 0;
+        loop {
+            date = match self {
+                Recurrence::Daily => date.checked_add_days(Days::new(1))?,
+                // Counted from the start so the 31st comes back after
+                // a shorter month
+                Recurrence::Monthly => {
+                    months += 1;
+                    start.checked_add_months(Months::new(months))?
+                }
+                _ => date.checked_add_days(Days::new(7))?,
+            };
+            if date > done {
+                return Some(date);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly(days) if days.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly(days) => {
+                let names: Vec<String> =
+                    days.iter().map(|d| d.to_string().to_lowercase()).collect();
+                write!(f, "{}", names.join(","))
+            }
+            Recurrence::Monthly => write!(f, "monthly"),
+            Recurrence::AfterDays(days) if days % 7 == 0 => write!(f, "{}w", days / 7),
+            Recurrence::AfterDays(days) => write!(f, "{}d", days),
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.as_str() {
+            "daily" => return Ok(Recurrence::Daily),
+            "weekly" => return Ok(Recurrence::Weekly(Vec::new())),
+            "monthly" => return Ok(Recurrence::Monthly),
+            _ => {}
+        }
+        let after = |suffix: char, days: u32| {
+            s.strip_suffix(suffix)
+                .and_then(|n| n.parse::<u32>().ok())
+                .filter(|&n| n > 0)
+                .map(|n| n.checked_mul(days).filter(|&d| d <= MAX_DAYS))
+        };
+        match after('d', 1).or_else(|| after('w', 7)) {
+            Some(Some(days)) => return Ok(Recurrence::AfterDays(days)),
+            Some(None) => bail!("Repeat rule '{}' is longer than {} days", s, MAX_DAYS),
+            None => {}
+        }
+        let mut days = Vec::new();
+        for name in s.split(',') {
+            match name.parse::<Weekday>() {
+                Ok(day) if !days.contains(&day) => days.push(day),
+                Ok(_) => {}
+                Err(_) => bail!(
+                    "Unknown repeat rule '{}' (expected daily, weekly, monthly, weekdays like mon,thu, 3d or 2w)",
+                    s
+                ),
+            }
+        }
+        days.sort_by_key(|d| d.num_days_from_monday());
+        Ok(Recurrence::Weekly(days))
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(rule: Recurrence) -> String {
+        rule.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rule(s: &str) -> Recurrence {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_rules() {
+        for (input, printed) in [
+            ("daily", "daily"),
+            (" Weekly ", "weekly"),
+            ("monthly", "monthly"),
+            ("thu,MON,thu", "mon,thu"),
+            ("3d", "3d"),
+            ("14d", "2w"),
+            ("2w", "2w"),
+        ] {
+            assert_eq!(rule(input).to_string(), printed, "{:?}", input);
+            assert_eq!(rule(printed), rule(input));
+        }
+        let json = serde_json::to_string(&rule("mon,fri")).unwrap();
+        assert_eq!(json, "\"mon,fri\"");
+        assert_eq!(
+            serde_json::from_str::<Recurrence>(&json).unwrap(),
+            rule("fri,mon")
+        );
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        for bad in [
+            "",
+            "0d",
+            "-3d",
+            "fortnight",
+            "mon,,thu",
+            "mön",
+            "4000000000d",
+            "600w",
+        ] {
+            assert!(bad.parse::<Recurrence>().is_err(), "{:?}", bad);
+        }
+        assert!(serde_json::from_str::<Recurrence>("\"often\"").is_err());
+    }
+
+    #[test]
+    fn next_due_dates() {
+        // Sunday 2026-10-18, done a week after the due date
+        let due = Some(date(2026, 10, 11));
+        let done = date(2026, 10, 18);
+        assert_eq!(rule("daily").next_due(due, done), Some(date(2026, 10, 19)));
+        assert_eq!(rule("weekly").next_due(due, done), Some(date(2026, 10, 25)));
+        assert_eq!(
+            rule("mon,thu").next_due(due, done),
+            Some(date(2026, 10, 19))
+        );
+        assert_eq!(rule("3d").next_due(due, done), Some(date(2026, 10, 21)));
+        assert_eq!(rule("daily").next_due(None, done), Some(date(2026, 10, 19)));
+
+        // The 31st comes back after a shorter month
+        let monthly = rule("monthly");
+        let jan = date(2026, 1, 31);
+        assert_eq!(monthly.next_due(Some(jan), jan), Some(date(2026, 2, 28)));
+        assert_eq!(
+            monthly.next_due(Some(jan), date(2026, 2, 28)),
+            Some(date(2026, 3, 31))
+        );
+    }
+
+    #[test]
+    fn next_due_past_the_last_date() {
+        let last = NaiveDate::MAX;
+        assert_eq!(rule("daily").next_due(None, last), None);
+        assert_eq!(rule("monthly").next_due(None, last), None);
+        assert_eq!(rule("mon").next_due(None, last), None);
+        assert_eq!(rule("2w").next_due(None, last), None);
+    }
+}
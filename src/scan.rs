@@ -0,0 +1,239 @@
+//! Turns `TODO` / `FIXME` / `HACK` comments in git-tracked files into tasks.
+
+use crate::app::{SourceRef, Status, Task};
+use crate::git::{self, git};
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+const MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+const COMMENT_STARTS: [&str; 8] = ["//", "#", "/*", "/**", "--", ";", "<!--", "%"];
+const CONTEXT_LINES: usize = 2;
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A marker comment found in the source tree.
+pub struct Finding {
+    pub marker: String,
+    pub path: String,
+    pub line: usize, // 1-based
+    pub text: String,
+    pub context: String,
+    /// `HEAD`, unless the file has uncommitted changes.
+    pub commit: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ScanReport {
+    pub added: usize,
+    pub updated: usize,
+    pub missing: usize,
+}
+
+impl std::fmt::Display for ScanReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Scan: {} new, {} updated, {} missing",
+            self.added, self.updated, self.missing
+        )
+    }
+}
+
+/// Scans the repository and merges the findings into `tasks`.
+pub fn run(tasks: &mut Vec<Task>) -> Result<ScanReport> {
+    let root = git::repo_root().context("Scanning needs a git repository")?;
+    let files = git(&["ls-files", "-z"], Some(&root))?;
+    let head = git::head(&root);
+    let changed = git::changed_files(&root);
+
+    let mut findings = Vec::new();
+    for path in files.split('\0').filter(|p| !p.is_empty()) {
+        // Line numbers of an edited file are not those of `HEAD`
+        let commit = head.clone().filter(|_| !changed.iter().any(|c| c == path));
+        findings.extend(scan_file(&root, path, commit));
+    }
+    Ok(merge(tasks, findings))
+}
+
+fn scan_file(root: &Path, path: &str, commit: Option<String>) -> Vec<Finding> {
+    let full = root.join(path);
+    let too_big = fs::metadata(&full).map_or(true, |m| m.len() > MAX_FILE_SIZE);
+    // Unreadable or binary files are skipped silently
+    let content = match (too_big, fs::read_to_string(&full)) {
+        (false, Ok(content)) => content,
+        _ => return Vec::new(),
+    };
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut findings = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((marker, text)) = parse_marker(line) {
+            let from = i.saturating_sub(CONTEXT_LINES);
+            let to = (i + CONTEXT_LINES + 1).min(lines.len());
+            findings.push(Finding {
+                marker: marker.to_string(),
+                path: path.to_string(),
+                line: i + 1,
+                text,
+                context: lines[from..to].join("\n"),
+                commit: commit.clone(),
+            });
+        }
+    }
+    findings
+}
+
+/// Finds a `TODO`-style marker that opens a comment and returns the text
+/// after it, without the optional `(owner)` and `:`.
+fn parse_marker(line: &str) -> Option<(&'static str, String)> {
+    for marker in MARKERS {
+        let Some((pos, _)) = line.match_indices(marker).find(|&(pos, _)| {
+            let after = &line[pos + marker.len()..];
+            let word_end = after.is_empty() || after.starts_with([':', '(', ' ', '!', '-']);
+            word_end && follows_comment_start(&line[..pos])
+        }) else {
+            continue;
+        };
+        let after = &line[pos + marker.len()..];
+
+        let mut text = after;
+        if text.starts_with('(') {
+            text = text.split_once(')').map_or("", |(_, rest)| rest);
+        }
+        let text = text
+            .trim_start_matches([':', '!', '-', ' '])
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim();
+        return Some((marker, text.to_string()));
+    }
+    None
+}
+
+/// Whether only a comment opener and blanks come before the marker, as in
+/// `// TODO`, `/// TODO`, `# TODO` or ` * TODO` inside a block comment.
+fn follows_comment_start(before: &str) -> bool {
+    // `//!` and `#!` doc comments
+    let lead = before.trim_end().trim_end_matches('!');
+    let stars = lead.trim_start();
+    let continued = !stars.is_empty() && stars.chars().all(|c| c == '*');
+    continued || COMMENT_STARTS.iter().any(|c| lead.ends_with(c))
+}
+
+/// Matches findings to existing tasks by text (the line may have shifted),
+/// then by position (the text may have been edited). Leftover linked tasks
+/// are flagged as missing.
+fn merge(tasks: &mut Vec<Task>, findings: Vec<Finding>) -> ScanReport {
+    let mut report = ScanReport::default();
+    let mut seen = vec![false; tasks.len()];
+
+    let mut unmatched = Vec::new();
+    for finding in findings {
+        let by_text = tasks.iter().enumerate().position(|(i, t)| {
+            !seen[i]
+                && t.source
+                    .as_ref()
+                    .is_some_and(|s| s.path == finding.path && s.text == finding.text)
+        });
+        match by_text {
+            Some(i) => {
+                update(&mut tasks[i], &finding, &mut report);
+                seen[i] = true;
+            }
+            None => unmatched.push(finding),
+        }
+    }
+
+    for finding in unmatched {
+        let by_line = tasks.iter().enumerate().position(|(i, t)| {
+            !seen[i]
+                && t.source.as_ref().is_some_and(|s| {
+                    s.path == finding.path && s.line == finding.line && s.marker == finding.marker
+                })
+        });
+        match by_line {
+            Some(i) => {
+                update(&mut tasks[i], &finding, &mut report);
+                seen[i] = true;
+            }
+            None => {
+                tasks.push(new_task(&finding));
+                seen.push(true);
+                report.added += 1;
+            }
+        }
+    }
+
+    for (task, _) in tasks.iter_mut().zip(seen).filter(|(_, seen)| !seen) {
+        if let Some(source) = &mut task.source
+            && !source.missing
+        {
+            source.missing = true;
+            report.missing += 1;
+        }
+    }
+    report
+}
+
+fn update(task: &mut Task, finding: &Finding, report: &mut ScanReport) {
+    let Some(source) = &mut task.source else {
+        return;
+    };
+    // Follow an edited comment unless the task was renamed on the board
+    if source.text != finding.text
+        && task.title == title(&source.marker, &source.text, &source.path)
+    {
+        task.title = title(&finding.marker, &finding.text, &finding.path);
+    }
+    if source.snippet.is_some() && source.snippet == Some(fingerprint(&task.description)) {
+        task.description = description(finding);
+        source.snippet = Some(fingerprint(&task.description));
+    }
+    let changed = source.line != finding.line || source.text != finding.text || source.missing;
+    source.line = finding.line;
+    source.text = finding.text.clone();
+    source.marker = finding.marker.clone();
+    source.missing = false;
+    source.commit = finding.commit.clone();
+    if changed {
+        report.updated += 1;
+    }
+}
+
+fn title(marker: &str, text: &str, path: &str) -> String {
+    if text.is_empty() {
+        format!("{} in {}", marker, path)
+    } else {
+        format!("{}: {}", marker, text)
+    }
+}
+
+/// The code around the comment.
+fn description(finding: &Finding) -> String {
+    format!("```\n{}\n```", finding.context)
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`.
+fn fingerprint(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn new_task(finding: &Finding) -> Task {
+    let title = title(&finding.marker, &finding.text, &finding.path);
+    let mut task = Task::new(title, Status::Todo);
+    task.description = description(finding);
+    task.tags = vec![finding.marker.to_lowercase()];
+    task.source = Some(SourceRef {
+        path: finding.path.clone(),
+        line: finding.line,
+        marker: finding.marker.clone(),
+        text: finding.text.clone(),
+        commit: finding.commit.clone(),
+        missing: false,
+        snippet: Some(fingerprint(&task.description)),
+        extra: Default::default(),
+    });
+    task
+}
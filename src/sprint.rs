@@ -0,0 +1,217 @@
+//! Sprints: named date ranges that tasks are planned into (`Task::sprint`).
+//! `sprint start` records the estimate committed to, `sprint close` the
+//! estimate done, and unfinished tasks roll over into the next sprint.
+//! The burndown is rebuilt from the status changes recorded on the tasks.
+
+use crate::app::{Board, EstimateUnit, Status, Task, end_of_day};
+use anyhow::{Result, bail};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Sprint {
+    pub name: String,
+    pub start: NaiveDate,
+    /// Last day, inclusive.
+    pub end: NaiveDate,
+    /// Estimate of the sprint's tasks when it started.
+    #[serde(default)]
+    pub committed: u32,
+    /// Estimate done when it was closed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed: Option<NaiveDate>,
+    /// IDs of its tasks when it was closed, so rolling them over into the
+    /// next sprint does not change this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<Vec<u64>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Where a sprint stands now.
+pub struct Summary {
+    pub committed: u32,
+    /// Estimate of all its tasks, including ones added after the start.
+    pub scope: u32,
+    pub completed: u32,
+    pub tasks: usize,
+    pub done: usize,
+    pub unestimated: usize,
+}
+
+impl Sprint {
+    /// Running sprints (and ones closed before the IDs were recorded) hold
+    /// the tasks planned into them.
+    pub fn contains(&self, task: &Task) -> bool {
+        match &self.tasks {
+            Some(ids) => ids.contains(&task.id),
+            None => task.sprint.as_deref() == Some(self.name.as_str()),
+        }
+    }
+
+    /// Whether the task was done when the sprint closed, or is done now.
+    fn done(&self, task: &Task) -> bool {
+        match self.closed {
+            Some(day) => task.status_at(end_of_day(day)) == Some(Status::Done),
+            None => task.status == Status::Done,
+        }
+    }
+
+    pub fn summary(&self, tasks: &[Task]) -> Summary {
+        let tasks: Vec<&Task> = tasks.iter().filter(|t| self.contains(t)).collect();
+        let points = |t: &&Task| t.estimate.unwrap_or(0);
+        let done = tasks.iter().filter(|t| self.done(t));
+        Summary {
+            committed: self.committed,
+            scope: tasks.iter().map(points).sum(),
+            completed: self
+                .completed
+                .unwrap_or_else(|| done.clone().map(points).sum()),
+            tasks: tasks.len(),
+            done: done.count(),
+            unestimated: tasks.iter().filter(|t| t.estimate.is_none()).count(),
+        }
+    }
+
+    /// Estimate left at the end of each day from the start up to today
+    /// (or the end), counting the tasks that existed then.
+    pub fn burndown(&self, tasks: &[Task], today: NaiveDate) -> Vec<(NaiveDate, u32)> {
+        let last = self.end.min(today).min(self.closed.unwrap_or(self.end));
+        let mut days = Vec::new();
+        let mut day = self.start;
+        while day <= last {
+            let at = if day == today {
+                Utc::now()
+            } else {
+                end_of_day(day)
+            };
+            let remaining = tasks
+                .iter()
+                .filter(|t| self.contains(t))
+                .filter(|t| t.status_at(at).is_some_and(|s| s != Status::Done))
+                .map(|t| t.estimate.unwrap_or(0))
+                .sum();
+            days.push((day, remaining));
+            day += Duration::days(1);
+        }
+        days
+    }
+
+    /// `Oct 18 - Oct 31`
+    pub fn dates(&self) -> String {
+        format!(
+            "{} - {}",
+            self.start.format("%b %d"),
+            self.end.format("%b %d")
+        )
+    }
+}
+
+/// The running sprint.
+pub fn active(sprints: &[Sprint]) -> Option<&Sprint> {
+    sprints.iter().rev().find(|s| s.closed.is_none())
+}
+
+/// The running sprint, or else the last one closed.
+pub fn current(sprints: &[Sprint]) -> Option<&Sprint> {
+    active(sprints).or(sprints.last())
+}
+
+/// Starts sprint `name`, taking in the unfinished tasks of closed sprints.
+pub fn start(
+    board: &mut Board,
+    tasks: &mut [Task],
+    name: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    unit: EstimateUnit,
+) -> Result<String> {
+    if let Some(running) = active(&board.sprints) {
+        bail!("Sprint '{}' is still running; close it first", running.name);
+    }
+    if board.sprints.iter().any(|s| s.name == name) {
+        bail!("There already was a sprint called '{}'", name);
+    }
+    if end < start {
+        bail!("The sprint ends before it starts");
+    }
+
+    let mut rolled = 0;
+    for task in tasks.iter_mut().filter(|t| t.status != Status::Done) {
+        let closed = task
+            .sprint
+            .as_ref()
+            .is_some_and(|s| board.sprints.iter().any(|sprint| &sprint.name == s));
+        if closed {
+            task.sprint = Some(name.to_string());
+            task.touch();
+            rolled += 1;
+        }
+    }
+    let planned: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.sprint.as_deref() == Some(name) && t.status != Status::Done)
+        .collect();
+    let sprint = Sprint {
+        name: name.to_string(),
+        start,
+        end,
+        committed: planned.iter().map(|t| t.estimate.unwrap_or(0)).sum(),
+        completed: None,
+        closed: None,
+        tasks: None,
+        extra: Map::new(),
+    };
+    let mut message = format!(
+        "Started '{}' ({}): {} tasks, {} committed",
+        name,
+        sprint.dates(),
+        planned.len(),
+        unit.format(sprint.committed)
+    );
+    if rolled > 0 {
+        message.push_str(&format!(", {} rolled over", rolled));
+    }
+    board.sprints.push(sprint);
+    Ok(message)
+}
+
+/// Closes the running sprint on `today`. Its unfinished tasks join the
+/// next sprint that is started.
+pub fn close(
+    board: &mut Board,
+    tasks: &[Task],
+    today: NaiveDate,
+    unit: EstimateUnit,
+) -> Result<String> {
+    let Some(sprint) = board.sprints.iter_mut().rev().find(|s| s.closed.is_none()) else {
+        bail!("No sprint is running");
+    };
+    sprint.tasks = Some(
+        tasks
+            .iter()
+            .filter(|t| sprint.contains(t))
+            .map(|t| t.id)
+            .collect(),
+    );
+    sprint.closed = Some(today);
+    let summary = sprint.summary(tasks);
+    sprint.completed = Some(summary.completed);
+    let unfinished = summary.tasks - summary.done;
+    let mut message = format!(
+        "Closed '{}': {} of {} committed done",
+        sprint.name,
+        unit.format(summary.completed),
+        unit.format(summary.committed)
+    );
+    if unfinished > 0 {
+        message.push_str(&format!(
+            "; unfinished tasks rolling over into the next sprint: {}",
+            unfinished
+        ));
+    }
+    Ok(message)
+}
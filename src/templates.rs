@@ -0,0 +1,158 @@
+//! Task templates: a title prefix, description skeleton, tags, priority and
+//! checklist to start common kinds of tasks from. A few are built in; more
+//! are defined under `templates` in the global or per-repo config:
+//!
+//! ```json
+//! "templates": {
+//!   "bug": { "title": "Bug: ", "tags": ["bug"], "priority": "p2",
+//!            "description": "## Steps to reproduce\n",
+//!            "checklist": ["Reproduce", "Fix", "Add a regression test"] }
+//! }
+//! ```
+
+use crate::app::{Task, parse_priority};
+use crate::config::{Config, TemplateSpec};
+use anyhow::{Context, Result};
+
+#[derive(Clone, Debug)]
+pub struct Template {
+    pub name: String,
+    /// Put in front of the title, e.g. `Bug: `.
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub priority: Option<u8>,
+    /// Unchecked `- [ ]` items after the description.
+    pub checklist: Vec<String>,
+}
+
+impl Template {
+    /// The built-in templates with the configured ones on top, by name.
+    pub fn from_config(config: &Config) -> Result<Vec<Template>> {
+        let mut specs = built_in();
+        for (name, spec) in &config.templates {
+            specs.retain(|(n, _)| n != name);
+            specs.push((name.clone(), spec.clone()));
+        }
+        let mut templates = specs
+            .into_iter()
+            .map(|(name, spec)| {
+                let priority = spec
+                    .priority
+                    .as_deref()
+                    .map(parse_priority)
+                    .transpose()
+                    .with_context(|| format!("Template '{}'", name))?;
+                Ok(Template {
+                    name,
+                    title: spec.title,
+                    description: spec.description,
+                    tags: spec.tags,
+                    priority,
+                    checklist: spec.checklist,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// `title` with the prefix in front, unless it already starts with it.
+    pub fn prefixed(&self, title: &str) -> String {
+        if title.starts_with(self.title.trim()) {
+            title.to_string()
+        } else {
+            format!("{}{}", self.title, title).trim().to_string()
+        }
+    }
+
+    /// Adds the description, checklist, tags and priority to a task whose
+    /// own tags and priority win.
+    pub fn fill(&self, task: &mut Task) {
+        let mut description = self.description.trim_end().to_string();
+        if !self.checklist.is_empty() {
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            let items: Vec<String> = self
+                .checklist
+                .iter()
+                .map(|item| format!("- [ ] {}", item))
+                .collect();
+            description.push_str(&items.join("\n"));
+        }
+        task.description = description;
+        for tag in self.tags.iter().rev() {
+            if !task.tags.contains(tag) {
+                task.tags.insert(0, tag.clone());
+            }
+        }
+        task.priority = task.priority.or(self.priority);
+    }
+
+    /// `#bug P2 3 checklist items`, for the picker.
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = self.tags.iter().map(|t| format!("#{}", t)).collect();
+        if let Some(p) = self.priority {
+            parts.push(format!("P{}", p));
+        }
+        if !self.checklist.is_empty() {
+            parts.push(format!("{} checklist items", self.checklist.len()));
+        }
+        parts.join(" ")
+    }
+}
+
+fn built_in() -> Vec<(String, TemplateSpec)> {
+    let spec = |title: &str,
+                description: &str,
+                tags: &[&str],
+                priority: Option<&str>,
+                checklist: &[&str]| {
+        TemplateSpec {
+            title: title.to_string(),
+            description: description.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            priority: priority.map(String::from),
+            checklist: checklist.iter().map(|c| c.to_string()).collect(),
+        }
+    };
+    vec![
+        (
+            "bug".to_string(),
+            spec(
+                "Bug: ",
+                "## Steps to reproduce\n\n## Expected\n\n## Actual\n",
+                &["bug"],
+                Some("p2"),
+                &["Reproduce", "Fix", "Add a regression test"],
+            ),
+        ),
+        (
+            "release".to_string(),
+            spec(
+                "Release ",
+                "",
+                &["release"],
+                None,
+                &[
+                    "Update the changelog",
+                    "Bump the version",
+                    "Tag the release",
+                    "Publish",
+                    "Announce",
+                ],
+            ),
+        ),
+        (
+            "spike".to_string(),
+            spec(
+                "Spike: ",
+                "## Question\n\n## Findings\n",
+                &["spike"],
+                None,
+                &["Write up the findings", "Create follow-up tasks"],
+            ),
+        ),
+    ]
+}
@@ -0,0 +1,214 @@
+use crate::config::{Config, StyleSpec};
+use anyhow::{Result, anyhow, bail};
+use ratatui::style::{Color, Modifier, Style};
+
+/// Every style used by `ui::render`. Built-in themes fill all slots;
+/// user themes start from a built-in one and override individual slots.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub header: Style,
+    pub border: Style,
+    pub border_active: Style,
+    pub selected: Style,
+    pub footer: Style,
+    pub input: Style,
+    pub input_inactive: Style,
+    pub modal: Style,
+    pub modal_title: Style,
+    pub editor: Style,
+    pub danger: Style,
+    // Markdown in the view modal
+    pub heading: Style,
+    pub code: Style,
+    pub link: Style,
+    pub muted: Style,
+}
+
+const BUILT_IN: [&str; 4] = ["dark", "light", "high-contrast", "none"];
+
+impl Theme {
+    /// Picks the theme named in the config, `dark` without one. `NO_COLOR`
+    /// overrides either with the colorless theme.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        // Still resolved under NO_COLOR so a broken theme is reported
+        let theme = Self::resolve(config, config.theme.as_deref().unwrap_or("dark"), 0)?;
+        if no_color() {
+            return Self::resolve(config, "none", 0);
+        }
+        Ok(theme)
+    }
+
+    fn resolve(config: &Config, name: &str, depth: usize) -> Result<Self> {
+        if let Some(theme) = Self::built_in(name) {
+            return Ok(theme);
+        }
+        let spec = config.themes.get(name).ok_or_else(|| {
+            anyhow!(
+                "Unknown theme '{}' (built-in: {})",
+                name,
+                BUILT_IN.join(", ")
+            )
+        })?;
+        if depth > 8 {
+            bail!("Theme '{}' extends itself", name);
+        }
+
+        let mut theme =
+            Self::resolve(config, spec.extends.as_deref().unwrap_or("dark"), depth + 1)?;
+        for (slot, style) in &spec.styles {
+            let target = theme
+                .slot_mut(slot)
+                .ok_or_else(|| anyhow!("Unknown style '{}' in theme '{}'", slot, name))?;
+            *target = style
+                .to_style()
+                .map_err(|e| anyhow!("Theme '{}', style '{}': {}", name, slot, e))?;
+        }
+        Ok(theme)
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "header" => &mut self.header,
+            "border" => &mut self.border,
+            "border_active" => &mut self.border_active,
+            "selected" => &mut self.selected,
+            "footer" => &mut self.footer,
+            "input" => &mut self.input,
+            "input_inactive" => &mut self.input_inactive,
+            "modal" => &mut self.modal,
+            "modal_title" => &mut self.modal_title,
+            "editor" => &mut self.editor,
+            "danger" => &mut self.danger,
+            "heading" => &mut self.heading,
+            "code" => &mut self.code,
+            "link" => &mut self.link,
+            "muted" => &mut self.muted,
+            _ => return None,
+        })
+    }
+
+    fn built_in(name: &str) -> Option<Self> {
+        let bold = Modifier::BOLD;
+        let theme = match name {
+            "dark" => Theme {
+                header: Style::new().fg(Color::Cyan).add_modifier(bold),
+                border: Style::new().fg(Color::White),
+                border_active: Style::new().fg(Color::Yellow),
+                selected: Style::new().bg(Color::DarkGray).add_modifier(bold),
+                footer: Style::new().fg(Color::Gray),
+                input: Style::new().fg(Color::Green),
+                input_inactive: Style::new().fg(Color::White),
+                modal: Style::new().bg(Color::DarkGray),
+                modal_title: Style::new().fg(Color::Cyan).add_modifier(bold),
+                editor: Style::new().bg(Color::Black),
+                danger: Style::new().fg(Color::Red).bg(Color::Black),
+                heading: Style::new().fg(Color::Cyan).add_modifier(bold),
+                code: Style::new().fg(Color::White).bg(Color::Black),
+                link: Style::new()
+                    .fg(Color::LightBlue)
+                    .add_modifier(Modifier::UNDERLINED),
+                muted: Style::new().fg(Color::Gray),
+            },
+            "light" => Theme {
+                header: Style::new().fg(Color::Blue).add_modifier(bold),
+                border: Style::new().fg(Color::DarkGray),
+                border_active: Style::new().fg(Color::Blue).add_modifier(bold),
+                selected: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightBlue)
+                    .add_modifier(bold),
+                footer: Style::new().fg(Color::DarkGray),
+                input: Style::new().fg(Color::Green),
+                input_inactive: Style::new().fg(Color::DarkGray),
+                modal: Style::new().fg(Color::Black).bg(Color::Gray),
+                modal_title: Style::new().fg(Color::Blue).add_modifier(bold),
+                editor: Style::new().fg(Color::Black).bg(Color::White),
+                danger: Style::new().fg(Color::Red).bg(Color::White),
+                heading: Style::new().fg(Color::Blue).add_modifier(bold),
+                code: Style::new().fg(Color::Black).bg(Color::White),
+                link: Style::new()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::UNDERLINED),
+                muted: Style::new().fg(Color::DarkGray),
+            },
+            "high-contrast" => Theme {
+                header: Style::new().fg(Color::Yellow).add_modifier(bold),
+                border: Style::new().fg(Color::White),
+                border_active: Style::new().fg(Color::Yellow).add_modifier(bold),
+                selected: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(bold),
+                footer: Style::new().fg(Color::White),
+                input: Style::new().fg(Color::LightGreen).add_modifier(bold),
+                input_inactive: Style::new().fg(Color::White),
+                modal: Style::new().fg(Color::White).bg(Color::Black),
+                modal_title: Style::new().fg(Color::Yellow).add_modifier(bold),
+                editor: Style::new().fg(Color::White).bg(Color::Black),
+                danger: Style::new()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(bold),
+                heading: Style::new().fg(Color::Yellow).add_modifier(bold),
+                code: Style::new().fg(Color::Black).bg(Color::White),
+                link: Style::new()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::UNDERLINED),
+                muted: Style::new().fg(Color::White),
+            },
+            // Modifiers only, for NO_COLOR and monochrome terminals.
+            "none" => Theme {
+                header: Style::new().add_modifier(bold),
+                border: Style::new(),
+                border_active: Style::new().add_modifier(bold),
+                selected: Style::new().add_modifier(Modifier::REVERSED | bold),
+                footer: Style::new(),
+                input: Style::new().add_modifier(bold),
+                input_inactive: Style::new(),
+                modal: Style::new(),
+                modal_title: Style::new().add_modifier(bold),
+                editor: Style::new(),
+                danger: Style::new().add_modifier(bold),
+                heading: Style::new().add_modifier(bold),
+                code: Style::new().add_modifier(Modifier::REVERSED),
+                link: Style::new().add_modifier(Modifier::UNDERLINED),
+                muted: Style::new().add_modifier(Modifier::DIM),
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+}
+
+/// https://no-color.org: any non-empty value disables color.
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+impl StyleSpec {
+    fn to_style(&self) -> Result<Style> {
+        let mut style = Style::new();
+        if let Some(fg) = &self.fg {
+            style = style.fg(parse_color(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(parse_color(bg)?);
+        }
+        for (on, modifier) in [
+            (self.bold, Modifier::BOLD),
+            (self.italic, Modifier::ITALIC),
+            (self.underline, Modifier::UNDERLINED),
+            (self.reversed, Modifier::REVERSED),
+        ] {
+            if on {
+                style = style.add_modifier(modifier);
+            }
+        }
+        Ok(style)
+    }
+}
+
+fn parse_color(s: &str) -> Result<Color> {
+    s.parse()
+        .map_err(|_| anyhow!("Invalid color '{}' (use a name, 0-255 or #rrggbb)", s))
+}
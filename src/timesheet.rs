@@ -0,0 +1,231 @@
+//! Time tracked on tasks with the timer key, and the report that adds it
+//! up per task, tag and day (`git kanban report`).
+
+use crate::app::Task;
+use crate::csv;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    /// `None` while the timer runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    /// Running entries count up to `now`.
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        (self.end.unwrap_or(now) - self.start).max(Duration::zero())
+    }
+}
+
+/// Index of the task whose timer runs and when it started.
+pub fn running(tasks: &[Task]) -> Option<(usize, DateTime<Utc>)> {
+    tasks.iter().enumerate().find_map(|(i, task)| {
+        task.time_entries
+            .iter()
+            .find(|e| e.end.is_none())
+            .map(|e| (i, e.start))
+    })
+}
+
+/// Stops the task's timer; `false` if it was not running.
+pub fn stop(task: &mut Task, now: DateTime<Utc>) -> bool {
+    match task.time_entries.iter_mut().find(|e| e.end.is_none()) {
+        Some(entry) => {
+            entry.end = Some(now);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn total(task: &Task, now: DateTime<Utc>) -> Duration {
+    task.time_entries.iter().map(|e| e.duration(now)).sum()
+}
+
+/// `0:05:09`, for the running timer.
+pub fn clock(d: Duration) -> String {
+    let s = d.num_seconds().max(0);
+    format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+}
+
+/// `2h 05m` or `12m`, for totals.
+pub fn hours(d: Duration) -> String {
+    let m = d.num_minutes().max(0);
+    if m < 60 {
+        format!("{}m", m)
+    } else {
+        format!("{}h {:02}m", m / 60, m % 60)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Group {
+    Task,
+    Tag,
+    Day,
+}
+
+impl Group {
+    pub const ALL: [Group; 3] = [Group::Task, Group::Tag, Group::Day];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Group::Task => "task",
+            Group::Tag => "tag",
+            Group::Day => "day",
+        }
+    }
+}
+
+impl std::str::FromStr for Group {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "task" => Ok(Group::Task),
+            "tag" => Ok(Group::Tag),
+            "day" => Ok(Group::Day),
+            _ => bail!("Unknown grouping '{}' (expected task, tag or day)", s),
+        }
+    }
+}
+
+/// Tracked time by task (in ID order), tag and local day.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub tasks: Vec<(String, Duration)>,
+    pub tags: Vec<(String, Duration)>,
+    pub days: Vec<(String, Duration)>,
+    pub total: Duration,
+}
+
+impl Report {
+    /// Time between `since` and `until` (inclusive local dates). Entries
+    /// running over midnight count towards both days.
+    pub fn new(
+        tasks: &[Task],
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+        now: DateTime<Utc>,
+    ) -> Report {
+        let mut by_task = BTreeMap::new();
+        let mut by_tag = BTreeMap::new();
+        let mut by_day = BTreeMap::new();
+        let mut total = Duration::zero();
+        for task in tasks {
+            let mut spent = Duration::zero();
+            for entry in &task.time_entries {
+                for (day, d) in split_days(entry, now) {
+                    if since.is_some_and(|s| day < s) || until.is_some_and(|u| day > u) {
+                        continue;
+                    }
+                    *by_day.entry(day).or_insert_with(Duration::zero) += d;
+                    spent += d;
+                }
+            }
+            if spent.is_zero() {
+                continue;
+            }
+            total += spent;
+            *by_task
+                .entry((task.id, task.title.clone()))
+                .or_insert_with(Duration::zero) += spent;
+            if task.tags.is_empty() {
+                *by_tag
+                    .entry("(untagged)".to_string())
+                    .or_insert_with(Duration::zero) += spent;
+            }
+            for tag in &task.tags {
+                *by_tag.entry(tag.clone()).or_insert_with(Duration::zero) += spent;
+            }
+        }
+        Report {
+            tasks: by_task
+                .into_iter()
+                .map(|((id, title), d)| (format!("#{} {}", id, title), d))
+                .collect(),
+            tags: by_tag.into_iter().collect(),
+            days: by_day
+                .into_iter()
+                .map(|(day, d)| (day.to_string(), d))
+                .collect(),
+            total,
+        }
+    }
+
+    fn rows(&self, group: Group) -> &[(String, Duration)] {
+        match group {
+            Group::Task => &self.tasks,
+            Group::Tag => &self.tags,
+            Group::Day => &self.days,
+        }
+    }
+
+    /// A section per grouping with aligned totals.
+    pub fn to_text(&self, groups: &[Group]) -> String {
+        if self.total.is_zero() {
+            return "No time tracked\n".to_string();
+        }
+        let mut out = String::new();
+        for &group in groups {
+            let rows = self.rows(group);
+            let width = rows
+                .iter()
+                .map(|(n, _)| n.chars().count())
+                .max()
+                .unwrap_or(0);
+            out.push_str(&format!("By {}:\n", group.name()));
+            for (name, d) in rows {
+                out.push_str(&format!("  {:width$}  {:>8}\n", name, hours(*d)));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("Total: {}\n", hours(self.total)));
+        out
+    }
+
+    /// `group,name,minutes`, one row per task, tag or day.
+    pub fn to_csv(&self, groups: &[Group]) -> String {
+        let mut out = csv::write_record(&["group", "name", "minutes"]);
+        out.push('\n');
+        for &group in groups {
+            for (name, d) in self.rows(group) {
+                out.push_str(&csv::write_record(&[
+                    group.name().to_string(),
+                    name.clone(),
+                    d.num_minutes().to_string(),
+                ]));
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// The entry cut at local midnights.
+fn split_days(entry: &TimeEntry, now: DateTime<Utc>) -> Vec<(NaiveDate, Duration)> {
+    let mut parts = Vec::new();
+    let mut start = entry.start.with_timezone(&Local);
+    let end = entry.end.unwrap_or(now).with_timezone(&Local);
+    while start < end {
+        let day = start.date_naive();
+        let midnight = (day + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| Local.from_local_datetime(&t).earliest())
+            .unwrap_or(end);
+        let stop = if midnight > start {
+            midnight.min(end)
+        } else {
+            end
+        };
+        parts.push((day, stop - start));
+        start = stop;
+    }
+    parts
+}
@@ -1,13 +1,33 @@
-use crate::app::{App, EditFocus};
+use crate::app::{
+    App, ColumnLayout, Density, EditFocus, EstimateUnit, Status, Task, items_fitting, max_offset,
+};
+use crate::epics;
+use crate::export::Format;
+use crate::flow;
+use crate::keymap::Action;
+use crate::markdown;
+use crate::modes::Mode;
+use crate::sprint;
+use crate::theme::Theme;
+use crate::timesheet;
+use chrono::Local;
 use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthChar;
 
-pub fn render(f: &mut Frame, app: &App) {
+const CARD_TITLE_LINES: usize = 3;
+const CARD_PREVIEW_LINES: usize = 2;
+
+pub fn render(f: &mut Frame, app: &mut App) {
+    let theme = &app.theme;
+    // Quick add shows a preview line under its input
+    let footer_height = if app.is_open(Mode::QuickAdd) { 4 } else { 3 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),
             Constraint::Min(1),
-            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(footer_height),
         ])
         .split(f.area());
 
@@ -18,36 +38,91 @@ pub fn render(f: &mut Frame, app: &App) {
     } else {
         "Git Kanban (Local)"
     };
-    let title = Paragraph::new(title_text)
-        .style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(title, chunks[0]);
+    let column_titles = ["TODO", "DOING", "DONE"];
+    let timer = running_timer(app);
+    let layout = app.layout.resolve(chunks[1].width);
+    let single = app.zoomed || layout == ColumnLayout::Tabs;
+    app.tab_areas = [Rect::default(); 3];
+    if single {
+        // Tabs replace the title, which moves onto the border
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", title_text))
+            .title_top(timer)
+            .style(theme.header);
+        let inner = block.inner(chunks[0]);
+        f.render_widget(block, chunks[0]);
+        let mut spans = Vec::new();
+        let mut x = inner.x;
+        for (i, name) in column_titles.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled("│", theme.muted));
+                x += 1;
+            }
+            let label = format!(" {} {} ", name, app.get_tasks_in_column(i).len());
+            let width = Span::raw(&label).width() as u16;
+            app.tab_areas[i] = Rect::new(x, inner.y, width, 1).intersection(inner);
+            x += width;
+            let style = if i == app.active_column {
+                theme.selected
+            } else {
+                theme.header
+            };
+            spans.push(Span::styled(label, style));
+        }
+        if app.zoomed {
+            spans.push(Span::styled("  (zoomed)", theme.muted));
+        }
+        f.render_widget(Line::from(spans), inner);
+    } else {
+        let title = Paragraph::new(title_text)
+            .style(theme.header)
+            .block(Block::default().borders(Borders::ALL).title_top(timer));
+        f.render_widget(title, chunks[0]);
+    }
 
     // 2. Columns
-    let columns_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
-        ])
-        .split(chunks[1]);
-    let column_titles = ["TODO", "DOING", "DONE"];
+    app.column_areas = if single {
+        let mut areas = [Rect::default(); 3];
+        areas[app.active_column] = chunks[1];
+        areas
+    } else {
+        let direction = if layout == ColumnLayout::Stack {
+            Direction::Vertical
+        } else {
+            Direction::Horizontal
+        };
+        let split = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Ratio(1, 3); 3])
+            .split(chunks[1]);
+        [split[0], split[1], split[2]]
+    };
+    let columns_layout = app.column_areas;
+    let children = epics::children_by_parent(&app.tasks);
     for i in 0..3 {
+        if columns_layout[i].is_empty() {
+            continue;
+        }
         let tasks = app.get_tasks_in_column(i);
+        let card_width = columns_layout[i].width.saturating_sub(2);
         let items: Vec<ListItem> = tasks
             .iter()
-            .map(|t| ListItem::new(format!("• {}", t.title)))
+            .map(|t| {
+                let progress = epics::progress(&app.tasks, children.get(&t.id));
+                match app.density {
+                    Density::Compact => compact_item(t, progress, theme),
+                    Density::Cards => card_item(t, progress, card_width, app.estimate_unit, theme),
+                }
+            })
             .collect();
-        let is_modal = app.input_mode || app.delete_mode || app.view_mode || app.edit_mode;
-        let border_style = if app.active_column == i && !is_modal {
-            Style::default().fg(Color::Yellow)
+        let heights: Vec<u16> = items.iter().map(|item| item.height() as u16).collect();
+        let is_modal = app.mode() != Mode::Board;
+        let drop_target = app.drag.is_some_and(|d| d.over == Some(i));
+        let border_style = if (app.active_column == i && !is_modal) || drop_target {
+            theme.border_active
         } else {
-            Style::default().fg(Color::White)
+            theme.border
         };
         let list = List::new(items)
             .block(
@@ -56,138 +131,1364 @@ pub fn render(f: &mut Frame, app: &App) {
                     .title(column_titles[i])
                     .border_style(border_style),
             )
-            .highlight_style(
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .add_modifier(Modifier::BOLD),
-            );
-        if app.active_column == i {
-            let mut state = ListState::default();
-            state.select(Some(app.selected_index));
-            f.render_stateful_widget(list, columns_layout[i], &mut state);
-        } else {
-            f.render_widget(list, columns_layout[i]);
-        }
+            .highlight_style(theme.selected);
+        // States persist between frames so mouse hit-testing knows the scroll offset
+        let state = &mut app.list_states[i];
+        state.select((app.active_column == i).then_some(app.selected_index));
+        f.render_stateful_widget(list, columns_layout[i], state);
+        scroll_hints(
+            f,
+            columns_layout[i],
+            &heights,
+            state.offset(),
+            border_style,
+            theme.muted,
+        );
+        app.item_heights[i] = heights;
     }
 
-    // 3. Footer (Quick Add)
-    if app.input_mode {
+    // 2b. Status line
+    status_line(f, app, chunks[2]);
+
+    // 3. Footer (Quick Add / Filter)
+    if app.is_open(Mode::QuickAdd) {
+        let [input, preview] =
+            Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(chunks[3]);
+        let column = Status::ALL[app.active_column].label();
+        let kind = match app.quick_add_template {
+            Some(i) => app.templates[i].name.as_str(),
+            None => "Task",
+        };
         let mut editor = app.title_editor.clone();
         editor.set_block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" New Task (Enter to Save) ")
-                .style(Style::default().fg(Color::Green)),
+                .title(format!(" New {} in {} (Enter to Save) ", kind, column))
+                .style(theme.input),
         );
-        f.render_widget(&editor, chunks[2]);
+        f.render_widget(&editor, input);
+        f.render_widget(quick_add_preview(app), preview);
+    } else if let Some(palette) = &app.palette {
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(" : (Tab: Complete | ↑↓: History | Enter: Run | Esc: Cancel) ")
+            .style(theme.input);
+        if let Some(completion) = &palette.completion {
+            let choices: Vec<Span> = completion
+                .candidates
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let style = if i == completion.index {
+                        theme.selected
+                    } else {
+                        theme.input
+                    };
+                    Span::styled(format!(" {} ", c), style)
+                })
+                .collect();
+            block = block.title_bottom(Line::from(choices));
+        }
+        let mut editor = palette.editor.clone();
+        editor.set_block(block);
+        f.render_widget(&editor, chunks[3]);
+    } else if app.is_open(Mode::Filter) {
+        let mut editor = app.filter_editor.clone();
+        editor.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Filter: words, #tag (Enter: Keep | Esc: Clear) ")
+                .style(theme.input),
+        );
+        f.render_widget(&editor, chunks[3]);
     } else {
-        let help_text = "q:Quit | n:New | e:Edit | v:View | d:Delete | Shift+↑/↓:Move";
+        let help_text = app.keymap.footer_help(&[
+            ("Quit", &[Action::Quit]),
+            ("New", &[Action::New]),
+            ("Edit", &[Action::Edit]),
+            ("View", &[Action::View]),
+            ("Delete", &[Action::Delete]),
+            ("Move", &[Action::MoveUp, Action::MoveDown]),
+            ("Help", &[Action::Help]),
+        ]);
         let help = Paragraph::new(help_text)
-            .style(Style::default().fg(Color::Gray))
+            .style(theme.footer)
             .block(Block::default().borders(Borders::ALL));
-        f.render_widget(help, chunks[2]);
+        f.render_widget(help, chunks[3]);
     }
 
-    // 4. VIEW MODE
-    if app.view_mode {
-        let area = centered_rect(60, 60, f.area());
-        f.render_widget(Clear, area);
-        let block = Block::default()
-            .title(" Task Details (Esc to close) ")
-            .borders(Borders::ALL)
-            .style(Style::default().bg(Color::DarkGray));
-        let inner = block.inner(area);
-        f.render_widget(block, area);
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(2), Constraint::Min(1)])
-            .split(inner);
-        let (title_str, desc_str) = app.get_current_task_info();
-        let title_p = Paragraph::new(title_str).style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
-        );
-        f.render_widget(title_p, layout[0]);
-        let desc_text = if desc_str.is_empty() {
-            "(No description)"
+    // 4. Modals, bottom of the stack first so nested ones end up on top
+    for mode in app.modes.clone() {
+        match mode {
+            Mode::View => view_modal(f, app),
+            Mode::Edit => edit_modal(f, app),
+            Mode::Discard => discard_prompt(f, app),
+            Mode::Delete => delete_prompt(f, app),
+            Mode::DeleteEpic => delete_epic_prompt(f, app),
+            Mode::Locations => location_picker(f, app),
+            Mode::Templates => template_picker(f, app),
+            Mode::Export => export_dialog(f, app),
+            Mode::EditorError => editor_error(f, app),
+            Mode::Sprint => sprint_view(f, app),
+            Mode::Analytics => analytics_view(f, app),
+            Mode::Forecast => forecast_view(f, app),
+            Mode::Tree => task_tree(f, app),
+            Mode::Help => help_overlay(f, app),
+            Mode::Board | Mode::QuickAdd | Mode::Filter | Mode::Command => {}
+        }
+    }
+}
+
+/// Read-only task details with scrollable Markdown.
+fn view_modal(f: &mut Frame, app: &mut App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 60, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" Task Details (Esc: Close | e: Edit | d: Delete | Tab/Space: Checkbox | ?: Keys) ")
+        .borders(Borders::ALL)
+        .style(theme.modal);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let children = app
+        .current_task()
+        .map(|t| epics::children(&app.tasks, t.id))
+        .unwrap_or_default();
+    let children_height = match children.len() {
+        0 => 0,
+        n => n.min(5) as u16 + 2,
+    };
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Length(children_height),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+    let (title_str, desc_str) = app.get_current_task_info();
+    let mut header = vec![Line::styled(title_str, theme.modal_title)];
+    if let Some(task) = app.current_task().filter(|t| !t.tags.is_empty()) {
+        let tags: Vec<String> = task.tags.iter().map(|t| format!("#{}", t)).collect();
+        header.push(Line::styled(tags.join(" "), theme.muted));
+    }
+    if let Some(source) = app.current_task().and_then(|t| t.source.as_ref()) {
+        let gone = if source.missing {
+            " (comment removed)"
         } else {
-            &desc_str
+            ""
         };
-        let desc_p = Paragraph::new(desc_text).wrap(Wrap { trim: false });
-        let divider = Block::default().borders(Borders::TOP);
-        f.render_widget(divider, layout[1]);
-        let desc_area = Layout::default()
-            .constraints([Constraint::Min(1)])
-            .margin(1)
-            .split(layout[1])[0];
-        f.render_widget(desc_p, desc_area);
-    }
-
-    // 5. EDIT MODE
-    if app.edit_mode {
-        let area = centered_rect(80, 80, f.area());
-        f.render_widget(Clear, area);
-        let main_block = Block::default()
-            .title(" Edit Task (Tab: Switch | Ctrl+S: Save | Esc: Cancel) ")
-            .borders(Borders::ALL)
-            .style(Style::default().bg(Color::Black));
-        let inner = main_block.inner(area);
-        f.render_widget(main_block, area);
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(1)])
-            .split(inner);
-
-        // Title Editor
-        let title_color = if app.edit_focus == EditFocus::Title {
-            Color::Green
-        } else {
-            Color::White
+        header.push(Line::styled(
+            format!("{}:{}{}", source.path, source.line, gone),
+            theme.muted,
+        ));
+    }
+    if let Some(epic) = app
+        .current_task()
+        .and_then(|t| epics::parent(&app.tasks, t))
+    {
+        let epic = &app.tasks[epic];
+        header.push(Line::styled(
+            format!("In #{} {} (←: Open)", epic.id, epic.title),
+            theme.muted,
+        ));
+    }
+    let title_p = Paragraph::new(header);
+    f.render_widget(title_p, layout[0]);
+    if !children.is_empty() {
+        let done = children
+            .iter()
+            .filter(|&&i| app.tasks[i].status == Status::Done)
+            .count();
+        let items: Vec<ListItem> = children
+            .iter()
+            .map(|&i| {
+                let task = &app.tasks[i];
+                let style = match task.status {
+                    Status::Done => theme.muted,
+                    _ => Style::default(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<5} ", task.status.label()), theme.muted),
+                    Span::styled(task.title.clone(), style),
+                    Span::styled(format!(" #{}", task.id), theme.muted),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::TOP).title(format!(
+                " Children {}/{} done (c: Next | →: Open) ",
+                done,
+                children.len()
+            )))
+            .highlight_style(theme.selected);
+        let mut state = ListState::default().with_selected(Some(app.view_child));
+        f.render_stateful_widget(list, layout[1], &mut state);
+    }
+    let desc_text = if desc_str.is_empty() {
+        Text::from("(No description)")
+    } else {
+        markdown::render(
+            &desc_str,
+            theme,
+            inner.width.saturating_sub(2),
+            app.selected_checkbox_line(),
+        )
+    };
+    let divider = Block::default().borders(Borders::TOP);
+    f.render_widget(divider, layout[2]);
+    let desc_area = Layout::default()
+        .constraints([Constraint::Min(1)])
+        .margin(1)
+        .split(layout[2])[0];
+
+    // Wrapped height decides how far the description can scroll
+    let wrap = Wrap { trim: false };
+    let total = Paragraph::new(desc_text.clone())
+        .wrap(wrap)
+        .line_count(desc_area.width) as u16;
+    app.view_height = desc_area.height;
+    app.view_scroll_max = total.saturating_sub(desc_area.height);
+    if app.view_follow_checkbox
+        && let Some(line) = app.selected_checkbox_line()
+    {
+        let index = markdown::rendered_line(&desc_str, line).min(desc_text.lines.len());
+        let above = Text::from(desc_text.lines[..index].to_vec());
+        let top = Paragraph::new(above).wrap(wrap).line_count(desc_area.width) as u16;
+        if top < app.view_scroll {
+            app.view_scroll = top;
+        } else if top >= app.view_scroll + desc_area.height {
+            app.view_scroll = top + 1 - desc_area.height;
+        }
+    }
+    app.view_follow_checkbox = false;
+    app.view_scroll = app.view_scroll.min(app.view_scroll_max);
+
+    let desc_p = Paragraph::new(desc_text)
+        .wrap(wrap)
+        .scroll((app.view_scroll, 0));
+    f.render_widget(desc_p, desc_area);
+    if app.view_scroll_max > 0 {
+        let hint = Line::styled(" ↑↓ PgUp/PgDn: Scroll ", theme.muted).right_aligned();
+        let y = area.bottom() - 1;
+        f.render_widget(
+            hint,
+            Rect {
+                x: area.x + 1,
+                y,
+                width: area.width.saturating_sub(2),
+                height: 1,
+            },
+        );
+        // Drawn on the modal's right border
+        let track = Rect {
+            x: area.x,
+            width: area.width,
+            ..desc_area
         };
-        let mut t_editor = app.title_editor.clone();
-        t_editor.set_block(
+        scrollbar(
+            f,
+            track,
+            app.view_scroll_max as usize,
+            app.view_scroll as usize,
+            desc_area.height as usize,
+            theme.modal,
+        );
+    }
+}
+
+/// Title and description editors side by side.
+fn edit_modal(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 80, f.area());
+    f.render_widget(Clear, area);
+    let main_block = Block::default()
+        .title(" Edit Task (Tab: Switch | Ctrl+S: Save | Ctrl+E: $EDITOR | Esc: Cancel) ")
+        .borders(Borders::ALL)
+        .style(theme.editor);
+    let inner = main_block.inner(area);
+    f.render_widget(main_block, area);
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(inner);
+
+    // Title Editor
+    let title_style = if app.edit_focus == EditFocus::Title {
+        theme.input
+    } else {
+        theme.input_inactive
+    };
+    let mut t_editor = app.title_editor.clone();
+    t_editor.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Title ")
+            .style(title_style),
+    );
+    f.render_widget(&t_editor, layout[0]);
+
+    // Description Editor
+    let desc_style = if app.edit_focus == EditFocus::Description {
+        theme.input
+    } else {
+        theme.input_inactive
+    };
+    let mut d_editor = app.description_editor.clone();
+    d_editor.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Description ")
+            .style(desc_style),
+    );
+    f.render_widget(&d_editor, layout[1]);
+}
+
+fn delete_prompt(f: &mut Frame, app: &App) {
+    confirm_prompt(f, &app.theme, "Delete task?");
+}
+
+/// Deleting a task with children: keep them or delete them too.
+fn delete_epic_prompt(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let below = app
+        .current_task()
+        .map_or(0, |t| epics::children(&app.tasks, t.id).len());
+    let area = centered_rect(40, 25, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Confirm")
+        .borders(Borders::ALL)
+        .style(theme.danger);
+    let text = vec![
+        Line::from("Delete epic?"),
+        Line::from(format!("Child tasks: {}", below)),
+        Line::from(""),
+        Line::from("K: Keep them (move up a level)"),
+        Line::from("A: Delete them too"),
+        Line::from("N: Cancel"),
+    ];
+    let p = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(p, area);
+}
+
+/// Esc left the edit modal with unsaved changes.
+fn discard_prompt(f: &mut Frame, app: &App) {
+    confirm_prompt(f, &app.theme, "Discard unsaved changes?");
+}
+
+fn confirm_prompt(f: &mut Frame, theme: &Theme, question: &str) {
+    let area = centered_rect(30, 15, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Confirm")
+        .borders(Borders::ALL)
+        .style(theme.danger);
+    let text = vec![Line::from(question), Line::from(""), Line::from("Y / N")];
+    let p = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(p, area);
+}
+
+fn location_picker(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(picker) = &app.location_picker else {
+        return;
+    };
+    let area = centered_rect(60, 40, f.area());
+    f.render_widget(Clear, area);
+    let items: Vec<ListItem> = picker
+        .items
+        .iter()
+        .map(|l| {
+            let mut spans = vec![Span::raw(format!("{}:{}", l.path, l.line))];
+            if let Some(note) = &l.note {
+                spans.push(Span::styled(format!("  ({})", note), theme.muted));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(" Open Location (Enter: $EDITOR | Esc: Back) ")
+                .borders(Borders::ALL)
+                .style(theme.modal),
+        )
+        .highlight_style(theme.selected);
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// Every task below its epic with its status and the children done.
+fn task_tree(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(selected) = app.tree_selected else {
+        return;
+    };
+    let area = centered_rect(60, 70, f.area());
+    f.render_widget(Clear, area);
+    let children = epics::children_by_parent(&app.tasks);
+    let items: Vec<ListItem> = epics::tree(&app.tasks)
+        .into_iter()
+        .map(|(i, depth)| {
+            let task = &app.tasks[i];
+            let indent = match depth {
+                0 => String::new(),
+                _ => format!("{}└ ", "  ".repeat(depth - 1)),
+            };
+            let status = match task.status {
+                Status::Done => theme.muted,
+                Status::Doing => theme.heading,
+                Status::Todo => Style::default(),
+            };
+            let mut spans = vec![
+                Span::raw(indent),
+                Span::styled(format!("{:<5} ", task.status.label()), status),
+                Span::raw(task.title.clone()),
+                Span::styled(format!(" #{}", task.id), theme.muted),
+            ];
+            if let Some((done, total)) = epics::progress(&app.tasks, children.get(&task.id)) {
+                spans.push(Span::styled(
+                    format!("  ◆ {}/{}", done, total),
+                    theme.heading,
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(" Task Tree (Enter: Go to | Esc: Close) ")
+                .borders(Borders::ALL)
+                .style(theme.modal),
+        )
+        .highlight_style(theme.selected);
+    let mut state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn template_picker(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(selected) = app.template_picker else {
+        return;
+    };
+    let area = centered_rect(50, 40, f.area());
+    f.render_widget(Clear, area);
+    let items: Vec<ListItem> = app
+        .templates
+        .iter()
+        .map(|t| {
+            ListItem::new(Line::from(vec![
+                Span::raw(t.name.clone()),
+                Span::styled(format!("  {}", t.summary()), theme.muted),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
             Block::default()
+                .title(" New from Template (Enter: Use | Esc: Cancel) ")
                 .borders(Borders::ALL)
-                .title(" Title ")
-                .style(Style::default().fg(title_color)),
+                .style(theme.modal),
+        )
+        .highlight_style(theme.selected);
+    let mut state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn export_dialog(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(dialog) = &app.export_dialog else {
+        return;
+    };
+    let area = centered_rect(50, 30, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" Export (Tab: Format | Enter: Save | Ctrl+Y: Copy | Esc: Cancel) ")
+        .borders(Borders::ALL)
+        .style(theme.modal);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Length(3)])
+        .split(inner);
+    let formats: Vec<Span> = Format::ALL
+        .iter()
+        .map(|&format| {
+            let style = if format == dialog.format {
+                theme.selected
+            } else {
+                theme.muted
+            };
+            Span::styled(format!(" {} ", format.name()), style)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(Line::from(formats)), layout[0]);
+    let mut path = dialog.path.clone();
+    path.set_block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" File ")
+            .style(theme.input),
+    );
+    f.render_widget(&path, layout[1]);
+}
+
+/// `$EDITOR` failed or its output did not parse.
+fn editor_error(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some((message, _)) = &app.external_error else {
+        return;
+    };
+    let area = centered_rect(50, 25, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" External Editor ")
+        .borders(Borders::ALL)
+        .style(theme.danger);
+    let text = vec![
+        Line::from(message.as_str()),
+        Line::from(""),
+        Line::from("Enter: Edit again | Esc: Discard"),
+    ];
+    let p = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(p, area);
+}
+
+fn help_overlay(f: &mut Frame, app: &mut App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 80, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" Keys (↑↓ PgUp/PgDn: Scroll | Esc: Close) ")
+        .borders(Borders::ALL)
+        .style(theme.modal);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let text = help_text(app);
+    let total = text.lines.len() as u16;
+    let Some(help) = &mut app.help else {
+        return;
+    };
+    help.page = inner.height;
+    help.max = total.saturating_sub(inner.height);
+    help.scroll = help.scroll.min(help.max);
+    f.render_widget(Paragraph::new(text).scroll((help.scroll, 0)), inner);
+    if help.max > 0 {
+        scrollbar(
+            f,
+            Rect {
+                x: area.x,
+                width: area.width,
+                ..inner
+            },
+            help.max as usize,
+            help.scroll as usize,
+            inner.height as usize,
+            theme.modal,
         );
-        f.render_widget(&t_editor, layout[0]);
+    }
+}
 
-        // Description Editor
-        let desc_color = if app.edit_focus == EditFocus::Description {
-            Color::Green
+/// The current sprint: committed vs. completed estimate above a burndown
+/// of the remaining estimate against a straight line to zero.
+fn sprint_view(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(sprint) = sprint::current(&app.board.sprints) else {
+        return;
+    };
+    let unit = app.estimate_unit;
+    let area = centered_rect(80, 80, f.area());
+    f.render_widget(Clear, area);
+    let state = if sprint.closed.is_some() {
+        "closed"
+    } else {
+        "running"
+    };
+    let block = Block::default()
+        .title(format!(
+            " Sprint {} ({}, {}) ",
+            sprint.name,
+            sprint.dates(),
+            state
+        ))
+        .title_bottom(" Esc: Close ")
+        .borders(Borders::ALL)
+        .style(theme.modal);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let summary = sprint.summary(&app.tasks);
+    let mut lines = vec![Line::from(vec![
+        Span::styled("Committed ", theme.muted),
+        Span::raw(unit.format(summary.committed)),
+        Span::styled("   Scope ", theme.muted),
+        Span::raw(unit.format(summary.scope)),
+        Span::styled("   Completed ", theme.muted),
+        Span::raw(unit.format(summary.completed)),
+        Span::styled(
+            format!("   {} of {} tasks done", summary.done, summary.tasks),
+            theme.muted,
+        ),
+    ])];
+    if summary.unestimated > 0 {
+        lines.push(Line::styled(
+            format!("Tasks without an estimate: {}", summary.unestimated),
+            theme.danger,
+        ));
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(lines.len() as u16 + 1),
+            Constraint::Min(3),
+        ])
+        .split(inner);
+    f.render_widget(Paragraph::new(lines), rows[0]);
+
+    let today = Local::now().date_naive();
+    let length = (sprint.end - sprint.start).num_days().max(1) as f64;
+    let ideal = [(0.0, summary.committed as f64), (length, 0.0)];
+    let burndown = sprint.burndown(&app.tasks, today);
+    let remaining: Vec<(f64, f64)> = burndown
+        .iter()
+        .enumerate()
+        .map(|(i, (_, left))| (i as f64, *left as f64))
+        .collect();
+    let top = burndown
+        .iter()
+        .map(|(_, left)| *left)
+        .chain([summary.committed, summary.scope, 1])
+        .max()
+        .unwrap_or(1) as f64;
+    let datasets = vec![
+        Dataset::default()
+            .name("Ideal")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(theme.muted)
+            .data(&ideal),
+        Dataset::default()
+            .name("Remaining")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(theme.link)
+            .data(&remaining),
+    ];
+    let date = |d: chrono::NaiveDate| d.format("%b %d").to_string();
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .style(theme.muted)
+                .bounds([0.0, length])
+                .labels([date(sprint.start), date(sprint.end)]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(theme.muted)
+                .bounds([0.0, top])
+                .labels(["0".to_string(), unit.format(top as u32)]),
+        )
+        .legend_position(Some(LegendPosition::TopRight));
+    f.render_widget(chart, rows[1]);
+}
+
+/// Cumulative flow diagram on top, aging WIP below.
+fn analytics_view(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(range) = app.analytics_range else {
+        return;
+    };
+    let days = flow::RANGES[range];
+    let area = centered_rect(90, 90, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" Analytics ")
+        .title_bottom(format!(" Tab: {} days | Esc: Close ", days))
+        .borders(Borders::ALL)
+        .style(theme.modal);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(inner);
+    cumulative_flow_chart(f, app, days, rows[0]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[1]);
+    aging_wip(f, app, bottom[0], bottom[1]);
+}
+
+/// Tasks per column over time as stacked areas. A chart cannot fill
+/// areas, so each band is a dense run of bars from zero, the total first
+/// and DONE last so the lower bands are drawn over the higher ones.
+fn cumulative_flow_chart(f: &mut Frame, app: &App, days: i64, area: Rect) {
+    let theme = &app.theme;
+    let today = Local::now().date_naive();
+    let counts = flow::cumulative_flow(&app.tasks, days, today);
+    let last = (counts.len() - 1).max(1) as f64;
+    // Two braille dots per cell across the plot
+    let samples = (area.width as usize * 2).max(2);
+    let band = |columns: &[usize]| -> Vec<(f64, f64)> {
+        (0..samples)
+            .map(|i| {
+                let x = i as f64 * last / (samples - 1) as f64;
+                let (_, day) = counts[(x.round() as usize).min(counts.len() - 1)];
+                (x, columns.iter().map(|&c| day[c]).sum::<usize>() as f64)
+            })
+            .collect()
+    };
+    let bands = [band(&[0, 1, 2]), band(&[1, 2]), band(&[2])];
+    let styles = [theme.muted, theme.heading, theme.link.not_underlined()];
+    let datasets = Status::ALL
+        .iter()
+        .zip(bands.iter().zip(styles))
+        .map(|(status, (data, style))| {
+            Dataset::default()
+                .name(status.label())
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Bar)
+                .style(style)
+                .data(data)
+        })
+        .collect();
+    let top = counts
+        .iter()
+        .map(|(_, c)| c.iter().sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let date = |d: chrono::NaiveDate| d.format("%b %d").to_string();
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(" Cumulative flow "))
+        .x_axis(
+            Axis::default()
+                .style(theme.muted)
+                .bounds([0.0, last])
+                .labels([date(counts[0].0), date(today)]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(theme.muted)
+                .bounds([0.0, top as f64])
+                .labels(["0".to_string(), top.to_string()]),
+        )
+        .legend_position(Some(LegendPosition::TopLeft));
+    f.render_widget(chart, area);
+}
+
+/// Tasks in DOING by age against percentiles of the cycle time of
+/// finished tasks, with the oldest listed beside the chart.
+fn aging_wip(f: &mut Frame, app: &App, chart_area: Rect, list_area: Rect) {
+    let theme = &app.theme;
+    let now = chrono::Utc::now();
+    let wip = flow::aging_wip(&app.tasks, now);
+    let cycle_times = flow::cycle_times(&app.tasks);
+    let percentiles: Vec<(u8, f64)> = flow::PERCENTILES
+        .iter()
+        .filter_map(|&p| flow::percentile(&cycle_times, p).map(|v| (p, v)))
+        .collect();
+    let warn_after = percentiles.iter().find(|(p, _)| *p == 85).map(|(_, v)| *v);
+    let overdue = |age: f64| warn_after.is_some_and(|limit| age > limit);
+
+    let right = wip.len() as f64 + 1.0;
+    let lines: Vec<[(f64, f64); 2]> = percentiles
+        .iter()
+        .map(|&(_, v)| [(0.0, v), (right, v)])
+        .collect();
+    let (late, on_time): (Vec<_>, Vec<_>) = wip
+        .iter()
+        .enumerate()
+        .map(|(i, (_, age))| (i as f64 + 1.0, *age))
+        .partition(|(_, age)| overdue(*age));
+    let mut datasets: Vec<Dataset> = percentiles
+        .iter()
+        .zip(&lines)
+        .map(|((p, v), line)| {
+            Dataset::default()
+                .name(format!("{}%: {:.1}d", p, v))
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(theme.muted)
+                .data(line)
+        })
+        .collect();
+    for (points, style) in [(&on_time, theme.heading), (&late, theme.danger)] {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(style)
+                .data(points),
+        );
+    }
+    let top = wip
+        .iter()
+        .map(|(_, age)| *age)
+        .chain(percentiles.iter().map(|(_, v)| *v))
+        .fold(1.0, f64::max)
+        * 1.1;
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(" Aging WIP (days in DOING) "))
+        .x_axis(Axis::default().style(theme.muted).bounds([0.0, right]))
+        .y_axis(
+            Axis::default()
+                .style(theme.muted)
+                .bounds([0.0, top])
+                .labels(["0".to_string(), format!("{:.0}d", top)]),
+        )
+        .legend_position(Some(LegendPosition::TopRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(chart, chart_area);
+
+    let mut text = vec![Line::styled(
+        format!("Cycle time of {} finished tasks", cycle_times.len()),
+        theme.modal_title,
+    )];
+    if percentiles.is_empty() {
+        text.push(Line::styled(
+            "  none finished through DOING yet",
+            theme.muted,
+        ));
+    }
+    for (p, v) in &percentiles {
+        text.push(Line::from(format!("  {}% within {:.1} days", p, v)));
+    }
+    text.push(Line::from(""));
+    text.push(Line::styled(
+        format!("In progress: {}", wip.len()),
+        theme.modal_title,
+    ));
+    let width = list_area.width.saturating_sub(10) as usize;
+    for (i, (task, age)) in wip.iter().enumerate() {
+        let style = if overdue(*age) {
+            theme.danger
         } else {
-            Color::White
+            Style::default()
         };
-        let mut d_editor = app.description_editor.clone();
-        d_editor.set_block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Description ")
-                .style(Style::default().fg(desc_color)),
+        text.push(Line::from(vec![
+            Span::styled(format!("{:>2} {:>5.1}d ", i + 1, age), style),
+            Span::raw(truncate(&task.title, width)),
+        ]));
+    }
+    f.render_widget(Paragraph::new(text), list_area);
+}
+
+/// Both forecast answers over the throughput they are drawn from.
+fn forecast_view(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(forecast) = &app.forecast else {
+        return;
+    };
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(" Forecast ")
+        .title_bottom(" ↑/↓: Tasks | ←/→: Date | Esc: Close ")
+        .borders(Borders::ALL)
+        .style(theme.modal);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(5),
+            Constraint::Min(3),
+        ])
+        .split(inner);
+
+    let today = Local::now().date_naive();
+    let in_days = |date: chrono::NaiveDate| {
+        format!(
+            "{} ({} days)",
+            date.format("%a %b %d"),
+            (date - today).num_days()
+        )
+    };
+    f.render_widget(
+        Paragraph::new(Line::styled(
+            format!("Throughput: {}", forecast.throughput()),
+            theme.muted,
+        )),
+        rows[0],
+    );
+
+    let mut how_many = vec![Line::styled(
+        format!("How many tasks by {}", in_days(forecast.by)),
+        theme.modal_title,
+    )];
+    for (c, n) in &forecast.how_many {
+        how_many.push(Line::from(vec![
+            Span::styled(format!("  {:>2}%  ", c), theme.muted),
+            Span::raw(format!("{} or more", n)),
+        ]));
+    }
+    let mut when = vec![Line::styled(
+        format!("When will {} tasks be done", forecast.items),
+        theme.modal_title,
+    )];
+    for (c, date) in &forecast.when {
+        let answer = match date {
+            Some(date) => Span::raw(format!("by {}", in_days(*date))),
+            None => Span::styled("no throughput yet", theme.danger),
+        };
+        when.push(Line::from(vec![
+            Span::styled(format!("  {:>2}%  ", c), theme.muted),
+            answer,
+        ]));
+    }
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+    f.render_widget(Paragraph::new(how_many), columns[0]);
+    f.render_widget(Paragraph::new(when), columns[1]);
+
+    let data: Vec<u64> = forecast.history.iter().map(|&n| n as u64).collect();
+    let sparkline = Sparkline::default()
+        .block(Block::default().title(format!(" Done per day, last {} days ", data.len())))
+        .data(&data)
+        .style(theme.heading);
+    f.render_widget(sparkline, rows[2]);
+}
+
+/// One section per mode, built from the tables that dispatch the keys.
+fn help_text(app: &App) -> Text<'static> {
+    let theme = &app.theme;
+    let mut lines = Vec::new();
+    for mode in Mode::ALL {
+        let rows: Vec<(String, &str)> = if mode == Mode::Board {
+            Action::ALL
+                .iter()
+                .map(|a| (app.keymap.keys_for(*a).join("/"), a.description()))
+                .filter(|(keys, _)| !keys.is_empty())
+                .collect()
+        } else {
+            mode.bindings()
+                .iter()
+                .map(|b| (b.display_keys(), b.help))
+                .collect()
+        };
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::styled(mode.title(), theme.modal_title));
+        let width = rows
+            .iter()
+            .map(|(k, _)| k.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (keys, help) in rows {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<width$}  ", keys), theme.muted),
+                Span::raw(help),
+            ]));
+        }
+    }
+    Text::from(lines)
+}
+
+/// Left: the latest message until it times out. Right: task counts, the
+/// filter, the board name and where it is stored.
+fn status_line(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let mut summary = Vec::new();
+    for (i, name) in ["TODO", "DOING", "DONE"].iter().enumerate() {
+        let total = app.column_total(i);
+        let count = if app.filter.is_empty() {
+            total.to_string()
+        } else {
+            format!("{}/{}", app.get_tasks_in_column(i).len(), total)
+        };
+        summary.push(format!("{} {}", name, count));
+    }
+    let mut summary = vec![summary.join(" · ")];
+    if !app.filter.is_empty() {
+        summary.push(format!("filter: {}", app.filter));
+    }
+    summary.push(app.board_name());
+    summary.push(display_path(&app.file_path));
+    let summary = format!("{} ", summary.join(" │ "));
+
+    let summary_width = (Span::raw(&summary).width() as u16).min(area.width);
+    let [message_area, summary_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(summary_width)])
+            .spacing(1)
+            .areas(area);
+    f.render_widget(
+        Line::styled(summary, theme.muted).right_aligned(),
+        summary_area,
+    );
+    if let Some(message) = app.status_message() {
+        let style = if message.error {
+            theme.danger
+        } else {
+            theme.footer
+        };
+        f.render_widget(
+            Line::styled(format!(" {}", message.text), style),
+            message_area,
         );
-        f.render_widget(&d_editor, layout[1]);
     }
+}
 
-    // 6. Delete Confirmation
-    if app.delete_mode {
-        let area = centered_rect(30, 15, f.area());
-        f.render_widget(Clear, area);
-        let block = Block::default()
-            .title("Confirm")
-            .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Red).bg(Color::Black));
-        let text = vec![
-            Line::from("Delete task?"),
-            Line::from(""),
-            Line::from("Y / N"),
-        ];
-        let p = Paragraph::new(text)
-            .block(block)
-            .alignment(Alignment::Center);
-        f.render_widget(p, area);
+/// The storage path relative to the working directory or with `~` for the
+/// home directory.
+fn display_path(path: &std::path::Path) -> String {
+    if let Ok(cwd) = std::env::current_dir()
+        && let Ok(relative) = path.strip_prefix(&cwd)
+    {
+        return relative.display().to_string();
+    }
+    if let Some(home) = std::env::var_os("HOME")
+        && let Ok(relative) = path.strip_prefix(&home)
+    {
+        return format!("~/{}", relative.display());
+    }
+    path.display().to_string()
+}
+
+/// ` ⏱ Title 0:12:34 ` at the right of the header while a timer runs.
+fn running_timer<'a>(app: &App) -> Line<'a> {
+    match app.running_timer() {
+        Some((task, elapsed)) => Line::from(vec![
+            Span::raw(" ⏱ "),
+            Span::raw(truncate(&task.title, 30)),
+            Span::styled(format!(" {} ", timesheet::clock(elapsed)), app.theme.link),
+        ])
+        .right_aligned(),
+        None => Line::default(),
+    }
+}
+
+/// `• title ◆ 2/5 ↻ #tag`, with the children done on epics and `↻` on
+/// repeating tasks
+fn compact_item<'a>(task: &Task, progress: Option<(usize, usize)>, theme: &Theme) -> ListItem<'a> {
+    let missing = task.source.as_ref().is_some_and(|s| s.missing);
+    let bullet = if missing { "⚠" } else { "•" };
+    let mut spans = vec![Span::raw(format!("{} {}", bullet, task.title))];
+    if let Some((done, total)) = progress {
+        spans.push(Span::styled(
+            format!(" ◆ {}/{}", done, total),
+            theme.heading,
+        ));
+    }
+    if task.repeat.is_some() {
+        spans.push(Span::styled(" ↻", theme.muted));
+    }
+    for tag in &task.tags {
+        spans.push(Span::styled(format!(" #{}", tag), theme.muted));
+    }
+    let item = ListItem::new(Line::from(spans));
+    if missing {
+        item.style(theme.muted)
+    } else {
+        item
+    }
+}
+
+/// A box drawn with line characters (list items cannot hold a `Block`):
+/// the wrapped title, a short description preview and a metadata row.
+fn card_item<'a>(
+    task: &Task,
+    progress: Option<(usize, usize)>,
+    width: u16,
+    unit: EstimateUnit,
+    theme: &Theme,
+) -> ListItem<'a> {
+    let inner = (width as usize).saturating_sub(4).max(1);
+    let border = theme.muted;
+    let row = |mut spans: Vec<Span<'a>>| {
+        let used: usize = spans.iter().map(|s| s.width()).sum();
+        let mut line = vec![Span::styled("│ ", border)];
+        line.append(&mut spans);
+        line.push(Span::raw(" ".repeat(inner.saturating_sub(used))));
+        line.push(Span::styled(" │", border));
+        Line::from(line)
+    };
+
+    let mut lines = vec![Line::styled(format!("╭{}╮", "─".repeat(inner + 2)), border)];
+    let missing = task.source.as_ref().is_some_and(|s| s.missing);
+    let title_style = if missing {
+        theme.muted
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    };
+    let title = if missing {
+        format!("⚠ {}", task.title)
+    } else {
+        task.title.clone()
+    };
+    let mut title_lines = wrap_text(&title, inner);
+    if title_lines.len() > CARD_TITLE_LINES {
+        title_lines.truncate(CARD_TITLE_LINES);
+        let last = &mut title_lines[CARD_TITLE_LINES - 1];
+        *last = truncate(&format!("{}…", last), inner);
+    }
+    for line in title_lines {
+        lines.push(row(vec![Span::styled(line, title_style)]));
+    }
+
+    let preview = task
+        .description
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("```") && !l.starts_with("~~~"))
+        .take(CARD_PREVIEW_LINES);
+    for line in preview {
+        lines.push(row(vec![Span::styled(truncate(line, inner), theme.muted)]));
+    }
+
+    let meta = card_meta(task, progress, unit, theme);
+    if !meta.is_empty() {
+        let mut spans = Vec::new();
+        let mut used = 0;
+        for span in meta {
+            let w = span.width() + usize::from(!spans.is_empty());
+            if used + w > inner {
+                break;
+            }
+            if !spans.is_empty() {
+                spans.push(Span::raw(" "));
+            }
+            used += w;
+            spans.push(span);
+        }
+        lines.push(row(spans));
+    }
+    lines.push(Line::styled(format!("╰{}╯", "─".repeat(inner + 2)), border));
+    ListItem::new(lines)
+}
+
+/// The fields quick add parsed from the input so far.
+fn quick_add_preview<'a>(app: &App) -> Line<'a> {
+    let theme = &app.theme;
+    let parsed = app.parse_input();
+    let mut spans = Vec::new();
+    for tag in &parsed.tags {
+        spans.push(Span::styled(format!("#{}", tag), theme.link));
+    }
+    if let Some(p) = parsed.priority {
+        spans.push(Span::styled(format!("P{}", p), theme.danger));
+    }
+    if let Some(name) = &parsed.assignee {
+        spans.push(Span::styled(format!("@{}", name), theme.muted));
+    }
+    if let Some(due) = parsed.due {
+        let due = format!("due {}", due.format("%a %b %d"));
+        spans.push(Span::styled(due, theme.muted));
+    }
+    if let Some(estimate) = parsed.estimate {
+        spans.push(Span::styled(
+            app.estimate_unit.format(estimate),
+            theme.muted,
+        ));
+    }
+    if let Some(rule) = &parsed.repeat {
+        spans.push(Span::styled(format!("↻ {}", rule), theme.muted));
+    }
+    if let Some(name) = &parsed.sprint {
+        spans.push(Span::styled(format!("sprint {}", name), theme.muted));
+    }
+    if let Some(id) = parsed.parent {
+        spans.push(Span::styled(format!("in #{}", id), theme.muted));
+    }
+    for problem in &parsed.problems {
+        spans.push(Span::styled(format!("⚠ {}", problem), theme.danger));
+    }
+    if spans.is_empty() {
+        spans.push(Span::styled("#tag !p1 @name due:fri est:3", theme.muted));
+    }
+    let mut line = vec![Span::raw(" ")];
+    for span in spans {
+        line.push(span);
+        line.push(Span::raw("  "));
+    }
+    Line::from(line)
+}
+
+/// ID, epic progress or parent, priority, assignee, estimate, due date,
+/// checklist progress and tags.
+fn card_meta<'a>(
+    task: &Task,
+    progress: Option<(usize, usize)>,
+    unit: EstimateUnit,
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    let mut meta = vec![Span::styled(format!("#{}", task.id), theme.muted)];
+    if let Some((done, total)) = progress {
+        meta.push(Span::styled(format!("◆ {}/{}", done, total), theme.heading));
     }
+    if let Some(parent) = task.parent {
+        meta.push(Span::styled(format!("in #{}", parent), theme.muted));
+    }
+    if let Some(p) = task.priority {
+        meta.push(Span::styled(format!("P{}", p), theme.danger));
+    }
+    if let Some(name) = &task.assignee {
+        meta.push(Span::styled(format!("@{}", name), theme.muted));
+    }
+    if let Some(estimate) = task.estimate {
+        meta.push(Span::styled(unit.format(estimate), theme.muted));
+    }
+    if let Some(due) = task.due {
+        let overdue = task.status != Status::Done && due < Local::now().date_naive();
+        let style = if overdue { theme.danger } else { theme.muted };
+        meta.push(Span::styled(format!("due {}", due.format("%b %d")), style));
+    }
+    if let Some(rule) = &task.repeat {
+        meta.push(Span::styled(format!("↻ {}", rule), theme.muted));
+    }
+    if !task.time_entries.is_empty() {
+        let spent = timesheet::total(task, chrono::Utc::now());
+        meta.push(Span::styled(
+            format!("⏱ {}", timesheet::hours(spent)),
+            theme.muted,
+        ));
+    }
+    let (checked, total) = markdown::checkbox_progress(&task.description);
+    if total > 0 {
+        meta.push(Span::styled(
+            format!("☑ {}/{}", checked, total),
+            theme.muted,
+        ));
+    }
+    for tag in &task.tags {
+        meta.push(Span::styled(format!("#{}", tag), theme.link));
+    }
+    meta
+}
+
+/// Word-wraps `text` to `width` columns, breaking words that do not fit.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        let len = |s: &str| Span::raw(s).width();
+        if !line.is_empty() && len(&line) + 1 + len(&word) > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while len(&word) > width {
+            let (mut head, _) = split_at_width(&word, width);
+            // A wide character in a narrower column still has to go somewhere
+            if head.is_empty() {
+                head = &word[..word.chars().next().map_or(0, char::len_utf8)];
+            }
+            let head = head.to_string();
+            word = word[head.len()..].to_string();
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(head);
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if Span::raw(text).width() <= width {
+        return text.to_string();
+    }
+    let mut out = split_at_width(text, width.saturating_sub(1)).0.to_string();
+    out.push('…');
+    out
+}
+
+/// Splits `text` after as many characters as fit in `width` columns.
+fn split_at_width(text: &str, width: usize) -> (&str, &str) {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return text.split_at(i);
+        }
+    }
+    (text, "")
+}
+
+/// `↑ N more` / `↓ N more` on the borders of a list and a scrollbar on its
+/// right edge when not every item fits.
+fn scroll_hints(
+    f: &mut Frame,
+    area: Rect,
+    heights: &[u16],
+    offset: usize,
+    bar: Style,
+    hint: Style,
+) {
+    let rows = area.height.saturating_sub(2);
+    let total = heights.len();
+    let visible = items_fitting(heights, offset, rows);
+    if (offset == 0 && visible == total) || area.width < 4 {
+        return;
+    }
+    let above = offset;
+    let below = total.saturating_sub(offset + visible);
+    let hint_area = Rect {
+        x: area.x + 1,
+        width: area.width - 2,
+        height: 1,
+        ..area
+    };
+    if above > 0 {
+        let line = Line::styled(format!(" ↑ {} more ", above), hint).right_aligned();
+        f.render_widget(line, hint_area);
+    }
+    if below > 0 {
+        let line = Line::styled(format!(" ↓ {} more ", below), hint).right_aligned();
+        let y = area.bottom() - 1;
+        f.render_widget(line, Rect { y, ..hint_area });
+    }
+    let track = Rect {
+        y: area.y + 1,
+        height: area.height - 2,
+        ..area
+    };
+    scrollbar(f, track, max_offset(heights, rows), offset, visible, bar);
+}
+
+/// Vertical scrollbar on the right column of `area`.
+fn scrollbar(
+    f: &mut Frame,
+    area: Rect,
+    max_offset: usize,
+    offset: usize,
+    page: usize,
+    style: Style,
+) {
+    let bar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_symbol(Some(symbols::line::VERTICAL))
+        .style(style);
+    // One position per possible offset so the thumb reaches the end
+    let mut state = ScrollbarState::new(max_offset + 1)
+        .viewport_content_length(page)
+        .position(offset);
+    f.render_stateful_widget(bar, area, &mut state);
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {